@@ -1,96 +1,850 @@
+use iced::futures::{future::BoxFuture, stream::FuturesUnordered, FutureExt, StreamExt};
 use iced::subscription;
-use reqwest::{self, Client};
+use reqwest::{self, header, Client, StatusCode};
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use sha1::{Digest, Sha1};
 use std::{
-    fs::{self, File},
+    collections::{HashSet, VecDeque},
+    fs::{self, File, OpenOptions},
     hash::Hash,
     io::{BufReader, Read, Write},
     path::Path,
+    sync::{
+        atomic::{AtomicU64, AtomicU8, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant, SystemTime},
 };
 use zip::ZipArchive;
 
-#[cfg(target_os = "linux")]
-use std::os::unix::prelude::PermissionsExt;
-
 pub enum State {
-    GettingDownloadList(String, VersionType),
-    Downloading(DownloadList),
-    PreparingJavaDownload(Java),
-    DownloadingJava {
-        downloaded: u64,
-        total: u64,
-        download: reqwest::Response,
-        folder_to_store: String,
-        file_to_write: File,
-    },
-    ExtractingJava(String),
-    DownloadingMissingFiles(DownloadList),
+    GettingDownloadList(String, VersionType, usize, Endpoints, Control),
+    // forge and neoforge installs still have to run their installer once everything is downloaded.
+    Downloading(DownloadPool, Option<super::forge::Installer>),
+    RunningProcessors(super::forge::Installer, Control),
+    PreparingJavaDownload(String, usize, Endpoints, Control),
+    DownloadingJava(DownloadPool, super::java_runtime::JavaRuntime),
+    DownloadingMissingFiles(DownloadPool),
     Idle,
 }
 #[derive(Debug, Clone, PartialEq)]
 pub enum VersionType {
     Vanilla,
-    Fabric,
+    // with the loader version to install.
+    Fabric(String),
+    Quilt(String),
+    // with the full forge or neoforge version, like 1.20.1-47.2.0 or 20.4.80-beta.
+    Forge(String),
+    NeoForge(String),
+}
+
+impl VersionType {
+    // Name of the folder the version is installed to.
+    pub fn version_name(&self, game_version: &str) -> String {
+        match self {
+            VersionType::Vanilla => game_version.to_string(),
+            VersionType::Fabric(loader) => format!("{}-fabric-{}", game_version, loader),
+            VersionType::Quilt(loader) => format!("{}-quilt-{}", game_version, loader),
+            VersionType::Forge(loader) => format!(
+                "{}-forge-{}",
+                game_version,
+                loader
+                    .strip_prefix(&format!("{}-", game_version))
+                    .unwrap_or(loader)
+            ),
+            VersionType::NeoForge(loader) => format!("{}-neoforge-{}", game_version, loader),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoaderVersion {
+    pub version: String,
+    pub stable: bool,
+}
+
+impl std::fmt::Display for LoaderVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.stable {
+            write!(f, "{}", self.version)
+        } else {
+            write!(f, "{} (unstable)", self.version)
+        }
+    }
 }
 #[derive(Debug, Clone, PartialEq)]
 pub enum Progress {
-    GotDownloadList(i32),
-    Downloaded(i32),
+    GotDownloadList(TransferStats),
+    Downloaded(TransferStats),
     Finished,
+    // forge installer processors left to run.
+    RunningProcessors(usize),
 
-    StartedJavaDownload(u8),
-    JavaDownloadProgressed(u8, u8),
-    JavaDownloadFinished,
-    JavaExtracted,
+    StartedJavaDownload(TransferStats),
+    JavaDownloadProgressed(TransferStats),
+    JavaInstalled,
 
-    MissingFilesDownloadProgressed(u16),
+    MissingFilesDownloadProgressed(TransferStats),
     MissingFilesDownloadFinished,
 
+    // the downloader stopped after being cancelled and its part files were removed.
+    Cancelled,
+    VerificationFailed(String),
     Errored(String),
 }
 
+// How far a group of downloads got. Sizes come from the version and asset index jsons, so files
+// without a known size only count once they are downloaded.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct TransferStats {
+    pub downloaded_bytes: u64,
+    pub total_bytes: u64,
+    // bytes per second.
+    pub speed: u64,
+    pub remaining_files: usize,
+}
+
+impl TransferStats {
+    pub fn percentage(&self) -> u64 {
+        if self.total_bytes == 0 {
+            return 0;
+        }
+        (self.downloaded_bytes * 100 / self.total_bytes).min(100)
+    }
+
+    pub fn eta(&self) -> Option<Duration> {
+        if self.speed == 0 {
+            return None;
+        }
+        Some(Duration::from_secs(
+            self.total_bytes.saturating_sub(self.downloaded_bytes) / self.speed,
+        ))
+    }
+}
+
+impl std::fmt::Display for TransferStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mib = |bytes: u64| bytes as f64 / 1048576.0;
+        write!(
+            f,
+            "{:.1} / {:.1} MiB ({}%), {:.1} MiB/s, ",
+            mib(self.downloaded_bytes),
+            mib(self.total_bytes.max(self.downloaded_bytes)),
+            self.percentage(),
+            mib(self.speed)
+        )?;
+        match self.eta() {
+            Some(eta) if eta.as_secs() >= 60 => {
+                write!(f, "{}m {}s left", eta.as_secs() / 60, eta.as_secs() % 60)
+            }
+            Some(eta) => write!(f, "{}s left", eta.as_secs()),
+            None => write!(f, "{} files left", self.remaining_files),
+        }
+    }
+}
+
 pub fn start<I: 'static + Hash + Copy + Send + Sync>(
     id: I,
     version: String,
     version_type: VersionType,
+    concurrent_downloads: usize,
+    endpoints: Endpoints,
+    control: Control,
 ) -> iced::Subscription<(I, Progress)> {
     subscription::unfold(
         id,
-        State::GettingDownloadList(version, version_type),
+        State::GettingDownloadList(
+            version,
+            version_type,
+            concurrent_downloads,
+            endpoints,
+            control,
+        ),
         move |state| download(id, state),
     )
 }
-#[derive(Clone)]
-pub enum Java {
-    J8,
-    J17,
-}
+// Installs the java runtime component a version needs, like java-runtime-gamma.
 pub fn start_java<I: 'static + Hash + Copy + Send + Sync>(
     id: I,
-    java: Java,
+    component: String,
+    concurrent_downloads: usize,
+    endpoints: Endpoints,
+    control: Control,
 ) -> iced::Subscription<(I, Progress)> {
-    subscription::unfold(id, State::PreparingJavaDownload(java), move |state| {
-        download(id, state)
-    })
+    subscription::unfold(
+        id,
+        State::PreparingJavaDownload(component, concurrent_downloads, endpoints, control),
+        move |state| download(id, state),
+    )
 }
 
 pub fn start_missing_files<I: 'static + Hash + Copy + Send + Sync>(
     id: I,
     files: DownloadList,
 ) -> iced::Subscription<(I, Progress)> {
-    subscription::unfold(id, State::DownloadingMissingFiles(files), move |state| {
-        download(id, state)
-    })
+    subscription::unfold(
+        id,
+        State::DownloadingMissingFiles(DownloadPool::new(files)),
+        move |state| download(id, state),
+    )
 }
+
+// Base urls of every server the launcher downloads from. They are stored in the settings file,
+// so each one can be pointed at a mirror or a local cache.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Endpoints {
+    pub launcher_meta: String,
+    pub launcher_data: String,
+    pub resources: String,
+    pub libraries: String,
+    pub mojang_api: String,
+    pub fabric_meta: String,
+    pub fabric_maven: String,
+    pub quilt_meta: String,
+    pub quilt_maven: String,
+    pub forge_maven: String,
+    pub forge_files: String,
+    pub neoforge_maven: String,
+    pub maven_central: String,
+}
+
+impl Default for Endpoints {
+    fn default() -> Self {
+        Endpoints {
+            launcher_meta: String::from("https://piston-meta.mojang.com/"),
+            launcher_data: String::from("https://piston-data.mojang.com/"),
+            resources: String::from("https://resources.download.minecraft.net/"),
+            libraries: String::from("https://libraries.minecraft.net/"),
+            mojang_api: String::from("https://api.mojang.com/"),
+            fabric_meta: String::from("https://meta.fabricmc.net/"),
+            fabric_maven: String::from("https://maven.fabricmc.net/"),
+            quilt_meta: String::from("https://meta.quiltmc.org/"),
+            quilt_maven: String::from("https://maven.quiltmc.org/"),
+            forge_maven: String::from("https://maven.minecraftforge.net/"),
+            forge_files: String::from("https://files.minecraftforge.net/"),
+            neoforge_maven: String::from("https://maven.neoforged.net/"),
+            maven_central: String::from("https://repo1.maven.org/maven2/"),
+        }
+    }
+}
+
+impl Endpoints {
+    // Rewrites an url pointing at one of the official servers (including the ones found inside
+    // version jsons) so it goes through the configured endpoint instead.
+    pub fn resolve(&self, url: &str) -> String {
+        let hosts = [
+            ("https://piston-meta.mojang.com/", &self.launcher_meta),
+            ("https://launchermeta.mojang.com/", &self.launcher_meta),
+            ("https://piston-data.mojang.com/", &self.launcher_data),
+            ("https://launcher.mojang.com/", &self.launcher_data),
+            ("https://resources.download.minecraft.net/", &self.resources),
+            ("https://libraries.minecraft.net/", &self.libraries),
+            ("https://api.mojang.com/", &self.mojang_api),
+            ("https://meta.fabricmc.net/", &self.fabric_meta),
+            ("https://maven.fabricmc.net/", &self.fabric_maven),
+            ("https://meta.quiltmc.org/", &self.quilt_meta),
+            ("https://maven.quiltmc.org/", &self.quilt_maven),
+            ("https://maven.minecraftforge.net/", &self.forge_maven),
+            ("https://files.minecraftforge.net/", &self.forge_files),
+            ("https://maven.neoforged.net/", &self.neoforge_maven),
+            ("https://repo1.maven.org/maven2/", &self.maven_central),
+        ];
+
+        for (host, endpoint) in hosts {
+            if let Some(path) = url.strip_prefix(host) {
+                return format!("{}/{}", endpoint.trim_end_matches('/'), path);
+            }
+        }
+        url.to_string()
+    }
+
+    pub fn version_manifest(&self) -> String {
+        self.resolve("https://piston-meta.mojang.com/mc/game/version_manifest_v2.json")
+    }
+}
+
+// Used when the settings file doesn't say how many files to download at once.
+pub const DEFAULT_CONCURRENT_DOWNLOADS: usize = 8;
+
 #[derive(Clone)]
 pub struct DownloadList {
     pub download_list: Vec<Download>,
     pub client: Client,
+    pub concurrent_downloads: usize,
+    pub control: Control,
+}
+
+const RUNNING: u8 = 0;
+const PAUSED: u8 = 1;
+const CANCELLED: u8 = 2;
+
+// Shared between the launcher and a running downloader, so its downloads can be paused or
+// cancelled from the UI. The downloader checks it every time it reports progress.
+#[derive(Debug, Clone, Default)]
+pub struct Control(Arc<AtomicU8>);
+
+impl Control {
+    pub fn pause(&self) {
+        let _ = self
+            .0
+            .compare_exchange(RUNNING, PAUSED, Ordering::Relaxed, Ordering::Relaxed);
+    }
+
+    pub fn resume(&self) {
+        let _ = self
+            .0
+            .compare_exchange(PAUSED, RUNNING, Ordering::Relaxed, Ordering::Relaxed);
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(CANCELLED, Ordering::Relaxed)
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.0.load(Ordering::Relaxed) == PAUSED
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed) == CANCELLED
+    }
 }
 #[derive(Clone, Debug, PartialEq)]
 pub struct Download {
     pub path: String,
     pub url: String,
+    pub sha1: Option<String>,
+    pub size: Option<u64>,
+    // natives jars are extracted into their folder, skipping the entries that start with one of
+    // these (the version json's extract.exclude, like META-INF/). The jar is kept, so the
+    // extracted files can be verified against it.
+    pub extract: Option<Vec<String>>,
+}
+
+// How many times a file is downloaded again after failing verification.
+const VERIFICATION_RETRIES: u8 = 3;
+// How many times a transfer is retried after a network error, waiting twice as long each time.
+const DOWNLOAD_RETRIES: u32 = 5;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DownloadError {
+    Failed(String),
+    // the file kept not matching the hash or size from the version json.
+    Corrupted(String),
+    Cancelled,
+}
+
+impl std::fmt::Display for DownloadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DownloadError::Failed(e) | DownloadError::Corrupted(e) => write!(f, "{}", e),
+            DownloadError::Cancelled => write!(f, "the download was cancelled"),
+        }
+    }
+}
+
+impl From<DownloadError> for Progress {
+    fn from(error: DownloadError) -> Self {
+        match error {
+            DownloadError::Failed(e) => Progress::Errored(e),
+            DownloadError::Corrupted(e) => Progress::VerificationFailed(e),
+            DownloadError::Cancelled => Progress::Cancelled,
+        }
+    }
+}
+
+// How often progress is reported while big files are still being downloaded.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(500);
+
+// Runs up to `concurrent_downloads` transfers at the same time over the shared client,
+// starting the next queued file as soon as one finishes.
+pub struct DownloadPool {
+    queue: VecDeque<Download>,
+    running: FuturesUnordered<BoxFuture<'static, Result<Download, DownloadError>>>,
+    // files of the running transfers, put back in the queue when the pool is paused.
+    in_flight: Vec<Download>,
+    client: Client,
+    control: Control,
+    concurrent_downloads: usize,
+    // bytes written by every transfer, shared with them.
+    downloaded_bytes: Arc<AtomicU64>,
+    total_bytes: u64,
+    speed: u64,
+    last_sample: (Instant, u64),
+}
+
+impl DownloadPool {
+    pub fn new(list: DownloadList) -> Self {
+        // some assets share the same hash, and two transfers writing the same file would clash.
+        let mut seen_paths = HashSet::new();
+        let queue: VecDeque<Download> = list
+            .download_list
+            .into_iter()
+            .filter(|download| seen_paths.insert(download.path.clone()))
+            .collect();
+
+        let total_bytes = queue.iter().filter_map(|file: &Download| file.size).sum();
+
+        DownloadPool {
+            queue,
+            running: FuturesUnordered::new(),
+            in_flight: Vec::new(),
+            client: list.client,
+            control: list.control,
+            concurrent_downloads: list.concurrent_downloads.max(1),
+            downloaded_bytes: Arc::new(AtomicU64::new(0)),
+            total_bytes,
+            speed: 0,
+            last_sample: (Instant::now(), 0),
+        }
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.queue.len() + self.running.len()
+    }
+
+    pub fn stats(&mut self) -> TransferStats {
+        let downloaded_bytes = self.downloaded_bytes.load(Ordering::Relaxed);
+
+        // the speed is smoothed, so it doesn't jump around with every small file.
+        let (sample_time, sample_bytes) = self.last_sample;
+        let elapsed = sample_time.elapsed();
+        if elapsed >= PROGRESS_INTERVAL {
+            let current_speed = (downloaded_bytes.saturating_sub(sample_bytes) as f64
+                / elapsed.as_secs_f64()) as u64;
+            self.speed = match self.speed {
+                0 => current_speed,
+                speed => (speed * 7 + current_speed * 3) / 10,
+            };
+            self.last_sample = (Instant::now(), downloaded_bytes);
+        }
+
+        TransferStats {
+            downloaded_bytes,
+            total_bytes: self.total_bytes,
+            speed: self.speed,
+            remaining_files: self.remaining(),
+        }
+    }
+
+    // Tops up the running transfers and waits for the next one to finish, or for
+    // PROGRESS_INTERVAL to pass so progress can be shown while big files download.
+    // Returns None once every file was downloaded.
+    async fn next(&mut self) -> Option<Result<(), DownloadError>> {
+        if self.control.is_cancelled() {
+            self.cancel();
+            return Some(Err(DownloadError::Cancelled));
+        }
+        if self.control.is_paused() {
+            self.pause();
+            tokio::time::sleep(PROGRESS_INTERVAL).await;
+            return Some(Ok(()));
+        }
+
+        while self.running.len() < self.concurrent_downloads {
+            match self.queue.pop_front() {
+                Some(file) => {
+                    self.in_flight.push(file.clone());
+                    self.running.push(
+                        download_file(self.client.clone(), file, self.downloaded_bytes.clone())
+                            .boxed(),
+                    )
+                }
+                None => break,
+            }
+        }
+
+        match tokio::time::timeout(PROGRESS_INTERVAL, self.running.next()).await {
+            Ok(Some(Ok(file))) => {
+                self.in_flight.retain(|running| running.path != file.path);
+                Some(Ok(()))
+            }
+            Err(_) => Some(Ok(())),
+            Ok(Some(Err(e))) => Some(Err(e)),
+            Ok(None) => None,
+        }
+    }
+
+    // Stops the running transfers and puts their files back at the front of the queue.
+    // Their part files are kept, so they continue where they were once resumed.
+    fn pause(&mut self) {
+        // transfers that finished since they were last polled are done, only the others are
+        // queued again.
+        while let Some(Some(result)) = self.running.next().now_or_never() {
+            if let Ok(file) = result {
+                self.in_flight.retain(|running| running.path != file.path);
+            }
+        }
+        self.running = FuturesUnordered::new();
+        for file in self.in_flight.drain(..).rev() {
+            // download_file counts the part file again when it starts over.
+            self.downloaded_bytes.fetch_sub(
+                fs::metadata(format!("{}.part", file.path))
+                    .map(|m| m.len())
+                    .unwrap_or(0),
+                Ordering::Relaxed,
+            );
+            self.queue.push_front(file);
+        }
+    }
+
+    // Stops every transfer and removes the part files of the files that weren't finished.
+    fn cancel(&mut self) {
+        self.pause();
+        for file in self.queue.drain(..) {
+            let _ = fs::remove_file(format!("{}.part", file.path));
+        }
+    }
+}
+
+// Downloads a file on its own, for the ones that have to be read before the rest of the files
+// can be listed, like the asset index.
+pub async fn download_single_file(
+    client: Client,
+    file: Download,
+) -> Result<Download, DownloadError> {
+    download_file(client, file, Arc::new(AtomicU64::new(0))).await
+}
+
+async fn download_file(
+    client: Client,
+    file: Download,
+    downloaded_bytes: Arc<AtomicU64>,
+) -> Result<Download, DownloadError> {
+    // files are written next to their final path and only renamed once they are complete,
+    // so a file that exists is never a half written one.
+    let part_path = format!("{}.part", file.path);
+    let mut failed_attempts = 0;
+    let mut corrupted_attempts = 0;
+
+    // whatever a previous launcher run left behind counts as downloaded.
+    downloaded_bytes.fetch_add(
+        fs::metadata(&part_path).map(|m| m.len()).unwrap_or(0),
+        Ordering::Relaxed,
+    );
+
+    loop {
+        println!("Downloading {}", file.path);
+
+        match fetch_to_part(&client, &file.url, &part_path, &downloaded_bytes).await {
+            Ok(()) => {}
+            Err(FetchError::Fatal(e)) => return Err(DownloadError::Failed(e)),
+            Err(FetchError::Transient(e)) => {
+                failed_attempts += 1;
+                if failed_attempts > DOWNLOAD_RETRIES {
+                    return Err(DownloadError::Failed(e));
+                }
+
+                let delay = Duration::from_secs(2u64.pow(failed_attempts - 1));
+                println!("{}: {e}, retrying in {:?}.", file.path, delay);
+                tokio::time::sleep(delay).await;
+                continue;
+            }
+        }
+
+        // hashing a big file takes a while, so it runs outside of the async executor.
+        let (path, sha1, size) = (part_path.clone(), file.sha1.clone(), file.size);
+        let verification =
+            match tokio::task::spawn_blocking(move || verify_file(&path, sha1.as_deref(), size))
+                .await
+            {
+                Ok(ok) => ok,
+                Err(e) => return Err(DownloadError::Failed(e.to_string())),
+            };
+        match verification {
+            Ok(()) => break,
+            Err(e) => {
+                downloaded_bytes.fetch_sub(
+                    fs::metadata(&part_path).map(|m| m.len()).unwrap_or(0),
+                    Ordering::Relaxed,
+                );
+                fs::remove_file(&part_path).ok();
+                corrupted_attempts += 1;
+                if corrupted_attempts > VERIFICATION_RETRIES {
+                    return Err(DownloadError::Corrupted(e));
+                }
+                println!("{e}, downloading it again.");
+            }
+        }
+    }
+
+    // natives are extracted before the rename, so a transfer that is stopped while extracting
+    // still has its part file and is counted right when it starts over.
+    if let Some(exclude) = file.extract.clone() {
+        let folder = match Path::new(&file.path).parent() {
+            Some(folder) => folder.to_path_buf(),
+            None => {
+                return Err(DownloadError::Failed(format!(
+                    "{} has no folder",
+                    file.path
+                )))
+            }
+        };
+        let archive_path = part_path.clone();
+        let extraction = match tokio::task::spawn_blocking(move || {
+            extract_archive(&archive_path, &folder, &exclude)
+        })
+        .await
+        {
+            Ok(ok) => ok,
+            Err(e) => return Err(DownloadError::Failed(e.to_string())),
+        };
+        if let Err(e) = extraction {
+            return Err(DownloadError::Failed(e));
+        }
+    };
+
+    if let Err(e) = fs::rename(&part_path, &file.path) {
+        return Err(DownloadError::Failed(e.to_string()));
+    }
+
+    Ok(file)
+}
+
+enum FetchError {
+    // worth trying again, like a dropped connection or a server error.
+    Transient(String),
+    Fatal(String),
+}
+
+// Streams `url` into `part_path`. If a previous attempt (or a previous launcher run) left part
+// of the file behind, only the rest of it is requested.
+async fn fetch_to_part(
+    client: &Client,
+    url: &str,
+    part_path: &str,
+    downloaded_bytes: &AtomicU64,
+) -> Result<(), FetchError> {
+    let resume_from = fs::metadata(part_path).map(|m| m.len()).unwrap_or(0);
+
+    let mut request = client.get(url);
+    if resume_from > 0 {
+        request = request.header(header::RANGE, format!("bytes={}-", resume_from));
+    }
+
+    let read_timeout = super::network::read_timeout();
+    let mut response = match tokio::time::timeout(read_timeout, request.send()).await {
+        Ok(Ok(ok)) => ok,
+        Ok(Err(e)) => return Err(FetchError::Transient(e.to_string())),
+        Err(_) => {
+            return Err(FetchError::Transient(format!(
+                "{url} didn't answer in time"
+            )))
+        }
+    };
+
+    let status = response.status();
+    if status == StatusCode::RANGE_NOT_SATISFIABLE && resume_from > 0 {
+        // the part file is already complete, verification will tell if it is any good.
+        return Ok(());
+    }
+    if status.is_server_error()
+        || status == StatusCode::REQUEST_TIMEOUT
+        || status == StatusCode::TOO_MANY_REQUESTS
+    {
+        return Err(FetchError::Transient(format!("{url} returned {status}")));
+    }
+    if !status.is_success() {
+        return Err(FetchError::Fatal(format!("{url} returned {status}")));
+    }
+
+    let output = if status == StatusCode::PARTIAL_CONTENT {
+        OpenOptions::new().append(true).open(part_path)
+    } else {
+        // the server sends the whole file again.
+        downloaded_bytes.fetch_sub(resume_from, Ordering::Relaxed);
+        File::create(part_path)
+    };
+    let mut output = match output {
+        Ok(ok) => ok,
+        Err(e) => return Err(FetchError::Fatal(e.to_string())),
+    };
+
+    loop {
+        match tokio::time::timeout(read_timeout, response.chunk()).await {
+            Ok(Ok(Some(chunk))) => match output.write_all(&chunk) {
+                Ok(ok) => {
+                    downloaded_bytes.fetch_add(chunk.len() as u64, Ordering::Relaxed);
+                    super::network::throttle(chunk.len() as u64).await;
+                    ok
+                }
+                Err(e) => return Err(FetchError::Fatal(e.to_string())),
+            },
+            Ok(Ok(None)) => return Ok(()),
+            Ok(Err(e)) => return Err(FetchError::Transient(e.to_string())),
+            Err(_) => return Err(FetchError::Transient(format!("{url} stopped sending data"))),
+        }
+    }
+}
+
+// Extracts a zip archive into a folder. Entries starting with one of the `exclude` prefixes are
+// skipped, and an entry whose path would end up outside of the folder fails the whole extraction.
+pub fn extract_archive(
+    archive_path: &str,
+    folder: &Path,
+    exclude: &[String],
+) -> Result<(), String> {
+    let archive_file = match File::open(archive_path) {
+        Ok(ok) => ok,
+        Err(e) => return Err(format!("{}: {}", archive_path, e)),
+    };
+    let mut archive = match ZipArchive::new(BufReader::new(archive_file)) {
+        Ok(ok) => ok,
+        Err(e) => return Err(format!("{}: {}", archive_path, e)),
+    };
+
+    for i in 0..archive.len() {
+        let mut entry = match archive.by_index(i) {
+            Ok(ok) => ok,
+            Err(e) => return Err(format!("{}: {}", archive_path, e)),
+        };
+        if exclude
+            .iter()
+            .any(|prefix| entry.name().starts_with(prefix.as_str()))
+        {
+            continue;
+        }
+
+        let outpath = match entry.enclosed_name() {
+            Some(path) => folder.join(path),
+            None => {
+                return Err(format!(
+                    "{} has an entry outside of its folder: {}",
+                    archive_path,
+                    entry.name()
+                ))
+            }
+        };
+
+        if entry.is_dir() {
+            if let Err(e) = fs::create_dir_all(&outpath) {
+                return Err(format!("{}: {}", outpath.display(), e));
+            }
+            continue;
+        }
+
+        if let Some(parent) = outpath.parent() {
+            if let Err(e) = fs::create_dir_all(parent) {
+                return Err(format!("{}: {}", parent.display(), e));
+            }
+        }
+        let mut outfile = match File::create(&outpath) {
+            Ok(ok) => ok,
+            Err(e) => return Err(format!("{}: {}", outpath.display(), e)),
+        };
+        if let Err(e) = std::io::copy(&mut entry, &mut outfile) {
+            return Err(format!("failed to extract {}: {}", outpath.display(), e));
+        }
+
+        #[cfg(target_os = "linux")]
+        if let Some(mode) = entry.unix_mode() {
+            use std::os::unix::prelude::PermissionsExt;
+            // only the permission bits, an archive shouldn't make setuid files.
+            let permissions = fs::Permissions::from_mode(mode & 0o777);
+            if let Err(e) = fs::set_permissions(&outpath, permissions) {
+                return Err(format!("{}: {}", outpath.display(), e));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// Compares the files extracted from an archive with its entries, returning the first one that is
+// missing or different.
+pub fn check_extracted(archive_path: &str, folder: &Path, exclude: &[String]) -> Option<String> {
+    let archive_file = match File::open(archive_path) {
+        Ok(ok) => ok,
+        Err(e) => return Some(format!("{}: {}", archive_path, e)),
+    };
+    let mut archive = match ZipArchive::new(BufReader::new(archive_file)) {
+        Ok(ok) => ok,
+        Err(e) => return Some(format!("{}: {}", archive_path, e)),
+    };
+
+    for i in 0..archive.len() {
+        let mut entry = match archive.by_index(i) {
+            Ok(ok) => ok,
+            Err(e) => return Some(format!("{}: {}", archive_path, e)),
+        };
+        if entry.is_dir()
+            || exclude
+                .iter()
+                .any(|prefix| entry.name().starts_with(prefix.as_str()))
+        {
+            continue;
+        }
+        // extraction fails on these, so there is nothing to compare.
+        let outpath = match entry.enclosed_name() {
+            Some(path) => folder.join(path),
+            None => continue,
+        };
+
+        let mut content = Vec::new();
+        if let Err(e) = entry.read_to_end(&mut content) {
+            return Some(format!("{}: {}", archive_path, e));
+        }
+        match fs::read(&outpath) {
+            Ok(extracted) if extracted == content => {}
+            Ok(_) => return Some(format!("{} was corrupted", outpath.display())),
+            Err(_) => return Some(format!("{} is missing", outpath.display())),
+        }
+    }
+    None
+}
+
+// Writes a whole file through a temporary one, so it is never left half written.
+pub fn write_file_atomically(path: &str, content: &[u8]) -> std::io::Result<()> {
+    let part_path = format!("{}.part", path);
+    fs::write(&part_path, content)?;
+    fs::rename(part_path, path)
+}
+
+// Checks a file against the size and sha1 from the version json, when they are known.
+pub fn verify_file(path: &str, sha1: Option<&str>, size: Option<u64>) -> Result<(), String> {
+    if let Some(size) = size {
+        let file_size = match fs::metadata(path) {
+            Ok(ok) => ok.len(),
+            Err(e) => return Err(format!("{}: {e}", path)),
+        };
+        if file_size != size {
+            return Err(format!(
+                "{} has {} bytes, expected {}",
+                path, file_size, size
+            ));
+        }
+    }
+
+    if let Some(sha1) = sha1 {
+        let file_sha1 = match file_sha1(path) {
+            Ok(ok) => ok,
+            Err(e) => return Err(format!("{}: {e}", path)),
+        };
+        if !file_sha1.eq_ignore_ascii_case(sha1) {
+            return Err(format!(
+                "{} has sha1 {}, expected {}",
+                path, file_sha1, sha1
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+pub fn file_sha1(path: &str) -> std::io::Result<String> {
+    let mut file = BufReader::new(File::open(path)?);
+    let mut hasher = Sha1::new();
+    std::io::copy(&mut file, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+// Reads the "sha1" and "size" fields that mojang (and fabric) put next to every download url.
+fn get_checksum(download: &Value) -> (Option<String>, Option<u64>) {
+    (
+        download["sha1"].as_str().map(|sha1| sha1.to_string()),
+        download["size"].as_u64(),
+    )
 }
 
 async fn download<I: 'static + Hash + Copy + Send + Sync>(
@@ -99,7 +853,13 @@ async fn download<I: 'static + Hash + Copy + Send + Sync>(
 ) -> ((I, Progress), State) {
     match state {
         // Versions downloading
-        State::GettingDownloadList(version, version_type) => {
+        State::GettingDownloadList(
+            version,
+            version_type,
+            concurrent_downloads,
+            endpoints,
+            control,
+        ) => {
             let mc_dir = match std::env::consts::OS {
                 "linux" => format!("{}/.minecraft", std::env::var("HOME").unwrap()),
                 "windows" => format!(
@@ -109,88 +869,172 @@ async fn download<I: 'static + Hash + Copy + Send + Sync>(
                 _ => panic!("System not supported."),
             };
 
-            let version_name = match version_type {
-                VersionType::Vanilla => version.clone(),
-                VersionType::Fabric => format!("{}-fabric", &version),
-            };
+            let version_name = version_type.version_name(&version);
 
             let version_folder = format!("{}/versions/{}", &mc_dir, version_name);
 
-            let client = Client::new();
+            let client = super::network::client();
 
-            // the fabric json doesn't provide all required files url, so we are going to get the vanilla json for fabric.
+            let mut installer = None;
+
+            // the fabric and quilt jsons don't provide all required files url, so we are going to get the vanilla json for them.
             let vanilla_version_json = match version_type {
                 VersionType::Vanilla => {
-                    match downloadversionjson(&version_type, &version, &version_folder, &client)
-                        .await
+                    match downloadversionjson(
+                        &version_type,
+                        &version,
+                        &version_folder,
+                        &client,
+                        &endpoints,
+                    )
+                    .await
                     {
                         Ok(json) => json,
-                        Err(e) => return ((id, Progress::Errored(e.to_string())), State::Idle),
+                        Err(e) => return ((id, Progress::Errored(e)), State::Idle),
                     }
                 }
-                VersionType::Fabric => {
-                    match downloadversionjson(&version_type, &version, &version_folder, &client)
-                        .await
+                VersionType::Fabric(_) | VersionType::Quilt(_) => {
+                    match downloadversionjson(
+                        &version_type,
+                        &version,
+                        &version_folder,
+                        &client,
+                        &endpoints,
+                    )
+                    .await
                     {
                         Ok(json) => json,
+                        Err(e) => return ((id, Progress::Errored(e)), State::Idle),
+                    };
+                    let vanilla_json_path = format!("{}/{}.json", version_folder, version);
+                    let content = match fs::read_to_string(&vanilla_json_path) {
+                        Ok(ok) => ok,
+                        Err(e) => {
+                            return (
+                                (
+                                    id,
+                                    Progress::Errored(format!("{}: {}", vanilla_json_path, e)),
+                                ),
+                                State::Idle,
+                            )
+                        }
+                    };
+                    match serde_json::from_str(&content) {
+                        Ok(ok) => ok,
+                        Err(e) => {
+                            return (
+                                (
+                                    id,
+                                    Progress::Errored(format!("{}: {}", vanilla_json_path, e)),
+                                ),
+                                State::Idle,
+                            )
+                        }
+                    }
+                }
+                VersionType::Forge(_) | VersionType::NeoForge(_) => {
+                    let vanilla_json =
+                        match download_vanilla_json(&version, &version_folder, &client, &endpoints)
+                            .await
+                        {
+                            Ok(json) => json,
+                            Err(e) => return ((id, Progress::Errored(e)), State::Idle),
+                        };
+
+                    let installer_path = format!(
+                        "{}/siglauncher_cache/installers/{}-installer.jar",
+                        mc_dir, version_name
+                    );
+                    match fs::create_dir_all(format!("{}/siglauncher_cache/installers", mc_dir)) {
+                        Ok(ok) => ok,
                         Err(e) => return ((id, Progress::Errored(e.to_string())), State::Idle),
+                    }
+                    let installer_download = Download {
+                        path: installer_path.clone(),
+                        url: endpoints
+                            .resolve(&super::forge::get_installer_url(&version_type).unwrap()),
+                        sha1: None,
+                        size: None,
+                        extract: None,
                     };
-                    let mut file =
-                        File::open(format!("{}/{}.json", version_folder, version)).unwrap();
-                    let mut fcontent = String::new();
-                    file.read_to_string(&mut fcontent).unwrap();
-                    let content = serde_json::from_str(&fcontent);
-                    content.unwrap()
+                    if let Err(e) = download_single_file(client.clone(), installer_download).await {
+                        return ((id, e.into()), State::Idle);
+                    }
+
+                    // the processors run with the same java the game would use.
+                    let (component, _) = super::java_runtime::get_required_java(&vanilla_json);
+                    let java_path = super::java_runtime::get_java_path(&component);
+
+                    match super::forge::Installer::new(
+                        &installer_path,
+                        &mc_dir,
+                        &version_folder,
+                        &version_name,
+                        &version,
+                        &java_path,
+                    ) {
+                        Ok(ok) => installer = Some(ok),
+                        Err(e) => return ((id, Progress::Errored(e)), State::Idle),
+                    }
+                    vanilla_json
                 }
             };
 
             let version_json = super::getjson(format!("{}/{}.json", version_folder, version_name));
 
             // asset index, we need this file to get assets
-            let asset_index_download = match client
-                .get(vanilla_version_json["assetIndex"]["url"].as_str().unwrap())
-                .send()
-                .await
-            {
-                Ok(ok) => ok.bytes().await.unwrap(),
-                Err(e) => return ((id, Progress::Errored(e.to_string())), State::Idle),
-            };
-
-            let asset_index_path = format!(
-                "{}/assets/indexes/{}.json",
-                mc_dir,
-                vanilla_version_json["assets"].as_str().unwrap()
-            );
-            match fs::create_dir_all(format!("{}/assets/indexes", mc_dir)) {
+            let asset_index = match get_asset_index(&mc_dir, &vanilla_version_json, &endpoints) {
                 Ok(ok) => ok,
-                Err(e) => return ((id, Progress::Errored(e.to_string())), State::Idle),
-            }
-            let mut asset_index_file = match File::create(&asset_index_path) {
-                Ok(ok) => ok,
-                Err(e) => return ((id, Progress::Errored(e.to_string())), State::Idle),
+                Err(e) => return ((id, Progress::Errored(e)), State::Idle),
             };
-
-            match asset_index_file.write_all(&asset_index_download) {
-                Ok(ok) => ok,
-                Err(e) => return ((id, Progress::Errored(e.to_string())), State::Idle),
+            if let Err(e) = download_single_file(client.clone(), asset_index.clone()).await {
+                return ((id, e.into()), State::Idle);
             }
 
-            let asset_index_json = super::getjson(asset_index_path);
+            let asset_index_json = super::getjson(asset_index.path);
 
             // variable to store download list
             let mut download_list = vec![];
 
+            // the installer processors need java, so it is downloaded with the rest of the files.
+            if let Some(installer) = &mut installer {
+                let (component, _) = super::java_runtime::get_required_java(&vanilla_version_json);
+                if installer.remaining() > 0 && !super::java_runtime::is_installed(&component) {
+                    match super::java_runtime::get_runtime_files(&client, &component, &endpoints)
+                        .await
+                    {
+                        Ok((files, runtime)) => {
+                            download_list.extend(files);
+                            installer.java_runtime = Some(runtime);
+                        }
+                        Err(e) => return ((id, Progress::Errored(e)), State::Idle),
+                    }
+                }
+            }
+
             // push the version jar
+            let (sha1, size) = get_checksum(&vanilla_version_json["downloads"]["client"]);
             download_list.push(Download {
                 path: format!("{}/{}.jar", version_folder, version_name),
-                url: vanilla_version_json["downloads"]["client"]["url"]
-                    .as_str()
-                    .unwrap()
-                    .to_string(),
+                url: endpoints.resolve(
+                    vanilla_version_json["downloads"]["client"]["url"]
+                        .as_str()
+                        .unwrap(),
+                ),
+                sha1,
+                size,
+                extract: None,
             });
 
+            // push the log4j configuration
+            match get_log_config(&mc_dir, &vanilla_version_json, &endpoints) {
+                Ok(Some(log_config)) => download_list.push(log_config),
+                Ok(None) => {}
+                Err(e) => return ((id, Progress::Errored(e)), State::Idle),
+            }
+
             // push assets
-            match get_assets(&mc_dir, asset_index_json) {
+            match get_assets(&mc_dir, asset_index_json, &endpoints) {
                 Ok(ok) => download_list.extend_from_slice(&ok),
                 Err(e) => return ((id, Progress::Errored(e.to_string())), State::Idle),
             }
@@ -201,6 +1045,7 @@ async fn download<I: 'static + Hash + Copy + Send + Sync>(
                 &mc_dir,
                 vanilla_version_json["libraries"].as_array().unwrap(),
                 &version_folder,
+                &endpoints,
             );
             let libraries = match libresult {
                 Ok(ok) => ok,
@@ -208,13 +1053,14 @@ async fn download<I: 'static + Hash + Copy + Send + Sync>(
             };
 
             download_list.extend_from_slice(libraries);
-            if version_type == VersionType::Fabric {
-                // fabric libraries
-                let libresult = &get_libraries(
-                    &mc_dir,
-                    version_json["libraries"].as_array().unwrap(),
-                    &version_folder,
-                );
+            if version_type != VersionType::Vanilla {
+                // loader libraries
+                let loader_libraries = match &installer {
+                    Some(installer) => &installer.libraries,
+                    None => version_json["libraries"].as_array().unwrap(),
+                };
+                let libresult =
+                    &get_libraries(&mc_dir, loader_libraries, &version_folder, &endpoints);
                 let libraries = match libresult {
                     Ok(ok) => ok,
                     Err(e) => return ((id, Progress::Errored(e.to_string())), State::Idle),
@@ -229,288 +1075,131 @@ async fn download<I: 'static + Hash + Copy + Send + Sync>(
                 }
             }
 
+            let mut pool = DownloadPool::new(DownloadList {
+                download_list: filtered_download_list,
+                client,
+                concurrent_downloads,
+                control,
+            });
             (
-                (
-                    id,
-                    Progress::GotDownloadList(filtered_download_list.len() as i32),
-                ),
-                State::Downloading(DownloadList {
-                    download_list: filtered_download_list,
-                    client,
-                }),
+                (id, Progress::GotDownloadList(pool.stats())),
+                State::Downloading(pool, installer),
             )
         }
 
-        State::Downloading(download_list) => {
-            if download_list.download_list.is_empty() {
-                println!("finished");
-                return ((id, Progress::Finished), State::Idle);
-            }
-
-            let mut list = download_list.download_list.clone();
-
-            let current_file_to_download = list.remove(0);
-            println!("Downloading {}", current_file_to_download.path);
-
-            let current_download = match download_list
-                .client
-                .get(current_file_to_download.url)
-                .send()
-                .await
-            {
-                Ok(ok) => ok.bytes().await.unwrap(),
-                Err(e) => return ((id, Progress::Errored(e.to_string())), State::Idle),
-            };
-
-            let mut file = match File::create(&current_file_to_download.path) {
-                Ok(file) => file,
-                Err(e) => return ((id, Progress::Errored(e.to_string())), State::Idle),
-            };
-
-            match file.write_all(&current_download) {
-                Ok(ok) => ok,
-                Err(e) => return ((id, Progress::Errored(e.to_string())), State::Idle),
-            }
-
-            if current_file_to_download.path.contains("natives.jar") {
-                let nativesfile = File::open(&current_file_to_download.path).unwrap();
-                let reader = BufReader::new(nativesfile);
-                let mut archive = ZipArchive::new(reader).unwrap();
-
-                let folder_to_store_natives =
-                    &current_file_to_download.path.replace("/natives.jar", "");
-
-                for i in 0..archive.len() {
-                    let mut file = archive.by_index(i).unwrap();
-                    let outpath = format!(
-                        "{}/{}",
-                        &folder_to_store_natives,
-                        file.mangled_name().to_string_lossy()
-                    );
-                    if file.is_dir() {
-                        println!("Creating directory: {:?}", outpath);
-                        std::fs::create_dir_all(&outpath).unwrap();
-                    } else {
-                        println!("Extracting file: {:?}", outpath);
-                        let mut outfile = File::create(&outpath).unwrap();
-                        std::io::copy(&mut file, &mut outfile).unwrap();
-                    }
+        State::Downloading(mut pool, installer) => match pool.next().await {
+            Some(Ok(())) => (
+                (id, Progress::Downloaded(pool.stats())),
+                State::Downloading(pool, installer),
+            ),
+            Some(Err(e)) => {
+                if let (DownloadError::Cancelled, Some(installer)) = (&e, installer) {
+                    installer.clean_up()
                 }
-                fs::remove_file(&current_file_to_download.path).unwrap();
-            };
-
-            println!("starting next download.");
-
-            (
-                (id, Progress::Downloaded(list.len() as i32)),
-                State::Downloading(DownloadList {
-                    download_list: list,
-                    client: download_list.client,
-                }),
-            )
-        }
-        // Idle
-        State::Idle => iced::futures::future::pending().await,
-        State::PreparingJavaDownload(java) => {
-            let os = std::env::consts::OS;
-            let java_url = match java{
-                Java::J8 => {
-                    match os{
-                        "windows" => "https://raw.githubusercontent.com/JafKc/siglauncher-jvm/main/binaries/java8-windows.zip",
-                        "linux" => "https://raw.githubusercontent.com/JafKc/siglauncher-jvm/main/binaries/java8-linux.zip",
-                        _ => panic!("Unsuported system.")
-                    }
-                },
-                Java::J17 => {
-                    match os{
-                        "windows" => "https://raw.githubusercontent.com/JafKc/siglauncher-jvm/main/binaries/java17-windows.zip",
-                        "linux" => "https://raw.githubusercontent.com/JafKc/siglauncher-jvm/main/binaries/java17-linux.zip",
-                        _ => panic!("Unsuported system.")
-                    }
-                },
-            };
-
-            let mc_dir = match std::env::consts::OS {
-                "linux" => format!("{}/.minecraft", std::env::var("HOME").unwrap()),
-                "windows" => format!(
-                    "{}/AppData/Roaming/.minecraft",
-                    std::env::var("USERPROFILE").unwrap().replace('\\', "/")
-                ),
-                _ => panic!("System not supported."),
-            };
-
-            let folder_to_store_download = format!("{}/siglauncher_java", mc_dir);
-
-            match fs::create_dir_all(&folder_to_store_download) {
-                Ok(ok) => ok,
-                Err(e) => return ((id, Progress::Errored(e.to_string())), State::Idle),
+                ((id, e.into()), State::Idle)
             }
-
-            let download = reqwest::get(java_url).await;
-
-            let file_to_write =
-                match File::create(format!("{}/compressed.zip", folder_to_store_download)) {
-                    Ok(ok) => ok,
-                    Err(e) => return ((id, Progress::Errored(e.to_string())), State::Idle),
-                };
-
-            match download {
-                Ok(d) => {
-                    let size = d.content_length().unwrap();
+            None => match installer {
+                Some(mut installer) if installer.remaining() > 0 => {
+                    if let Some(runtime) = installer.java_runtime.take() {
+                        if let Err(e) = runtime.finish() {
+                            return ((id, Progress::Errored(e)), State::Idle);
+                        }
+                    }
                     (
-                        (id, Progress::StartedJavaDownload((size / 1048576) as u8)),
-                        State::DownloadingJava {
-                            downloaded: 0,
-                            total: size,
-                            download: d,
-                            folder_to_store: folder_to_store_download,
-                            file_to_write,
-                        },
+                        (id, Progress::RunningProcessors(installer.remaining())),
+                        State::RunningProcessors(installer, pool.control),
                     )
                 }
-
-                Err(e) => ((id, Progress::Errored(e.to_string())), State::Idle),
-            }
-        }
-        State::DownloadingJava {
-            downloaded,
-            total,
-            mut download,
-            folder_to_store,
-            mut file_to_write,
-        } => match download.chunk().await {
-            Ok(Some(chunk)) => {
-                let downloaded = downloaded + chunk.len() as u64;
-                let percentage = ((downloaded as f32 / total as f32) * 100.0) as u8;
-                let mb_downloaded = (downloaded / 1048576) as u8;
-
-                match file_to_write.write_all(&chunk) {
-                    Ok(ok) => ok,
-                    Err(e) => return ((id, Progress::Errored(e.to_string())), State::Idle),
-                }
-                (
-                    (
-                        id,
-                        Progress::JavaDownloadProgressed(mb_downloaded, percentage),
-                    ),
-                    State::DownloadingJava {
-                        downloaded,
-                        total,
-                        download,
-                        folder_to_store,
-                        file_to_write,
-                    },
-                )
-            }
-            Ok(None) => (
-                (id, Progress::JavaDownloadFinished),
-                State::ExtractingJava(folder_to_store),
-            ),
-            Err(e) => ((id, Progress::Errored(e.to_string())), State::Idle),
-        },
-
-        State::ExtractingJava(folder) => {
-            let compressed_java = match File::open(format!("{}/compressed.zip", folder)) {
-                Ok(ok) => ok,
-                Err(e) => return ((id, Progress::Errored(e.to_string())), State::Idle),
-            };
-
-            let mut archive = ZipArchive::new(BufReader::new(compressed_java)).unwrap();
-
-            for i in 0..archive.len() {
-                let mut file = archive.by_index(i).unwrap();
-                let outpath = format!("{}/{}", &folder, file.mangled_name().to_string_lossy());
-                if file.is_dir() {
-                    std::fs::create_dir_all(&outpath).unwrap();
-                } else {
-                    let mut outfile = File::create(&outpath).unwrap();
-                    std::io::copy(&mut file, &mut outfile).unwrap();
-
-                    #[cfg(target_os = "linux")]
-                    if file.mangled_name().to_str().unwrap().ends_with("/java")
-                        && std::env::consts::OS == "linux"
-                    {
-                        let mut permission = fs::metadata(&outpath).unwrap().permissions();
-                        permission.set_mode(0o755);
-                        fs::set_permissions(&outpath, permission).unwrap();
+                _ => {
+                    if let Some(installer) = installer {
+                        installer.clean_up()
                     }
+                    println!("finished");
+                    ((id, Progress::Finished), State::Idle)
                 }
+            },
+        },
+        State::RunningProcessors(mut installer, control) => {
+            // a processor can't be stopped halfway, so pausing and cancelling wait for the
+            // running one to finish.
+            if control.is_cancelled() {
+                installer.clean_up();
+                return ((id, Progress::Cancelled), State::Idle);
+            }
+            if control.is_paused() {
+                tokio::time::sleep(PROGRESS_INTERVAL).await;
+                return (
+                    (id, Progress::RunningProcessors(installer.remaining())),
+                    State::RunningProcessors(installer, control),
+                );
             }
-            fs::remove_file(format!("{}/compressed.zip", folder)).unwrap();
-
-            ((id, Progress::JavaExtracted), State::Idle)
-        }
-        State::DownloadingMissingFiles(download_list) => {
-            if download_list.download_list.is_empty() {
-                println!("finished");
-                return ((id, Progress::MissingFilesDownloadFinished), State::Idle);
-            }
-
-            let mut list = download_list.download_list.clone();
-
-            let current_file_to_download = list.remove(0);
-            println!("Downloading {}", current_file_to_download.path);
-
-            let current_download = match download_list
-                .client
-                .get(current_file_to_download.url)
-                .send()
-                .await
-            {
-                Ok(ok) => ok.bytes().await.unwrap(),
-                Err(e) => return ((id, Progress::Errored(e.to_string())), State::Idle),
-            };
-
-            let mut file = match File::create(&current_file_to_download.path) {
-                Ok(file) => file,
-                Err(e) => return ((id, Progress::Errored(e.to_string())), State::Idle),
-            };
-
-            match file.write_all(&current_download) {
-                Ok(ok) => ok,
-                Err(e) => return ((id, Progress::Errored(e.to_string())), State::Idle),
-            }
-
-            if current_file_to_download.path.contains("natives.jar") {
-                let nativesfile = File::open(&current_file_to_download.path).unwrap();
-                let reader = BufReader::new(nativesfile);
-                let mut archive = ZipArchive::new(reader).unwrap();
-
-                let folder_to_store_natives =
-                    &current_file_to_download.path.replace("/natives.jar", "");
 
-                for i in 0..archive.len() {
-                    let mut file = archive.by_index(i).unwrap();
-                    let outpath = format!(
-                        "{}/{}",
-                        &folder_to_store_natives,
-                        file.mangled_name().to_string_lossy()
-                    );
-                    if file.is_dir() {
-                        println!("Creating directory: {:?}", outpath);
-                        std::fs::create_dir_all(&outpath).unwrap();
+            // processors can take a while, so they run outside of the async executor.
+            let result = tokio::task::spawn_blocking(move || {
+                let result = installer.run_next_processor();
+                (installer, result)
+            })
+            .await;
+
+            match result {
+                Ok((installer, Ok(()))) => {
+                    if installer.remaining() > 0 {
+                        (
+                            (id, Progress::RunningProcessors(installer.remaining())),
+                            State::RunningProcessors(installer, control),
+                        )
                     } else {
-                        println!("Extracting file: {:?}", outpath);
-                        let mut outfile = File::create(&outpath).unwrap();
-                        std::io::copy(&mut file, &mut outfile).unwrap();
+                        installer.clean_up();
+                        println!("finished");
+                        ((id, Progress::Finished), State::Idle)
                     }
                 }
-                fs::remove_file(&current_file_to_download.path).unwrap();
-            };
-
-            println!("starting next download.");
-
-            (
-                (
-                    id,
-                    Progress::MissingFilesDownloadProgressed(list.len() as u16),
-                ),
-                State::DownloadingMissingFiles(DownloadList {
-                    download_list: list,
-                    client: download_list.client,
-                }),
-            )
+                Ok((_, Err(e))) => ((id, Progress::Errored(e)), State::Idle),
+                Err(e) => ((id, Progress::Errored(e.to_string())), State::Idle),
+            }
+        }
+        // Idle
+        State::Idle => iced::futures::future::pending().await,
+        State::PreparingJavaDownload(component, concurrent_downloads, endpoints, control) => {
+            let client = super::network::client();
+            match super::java_runtime::get_runtime_files(&client, &component, &endpoints).await {
+                Ok((files, runtime)) => {
+                    let mut pool = DownloadPool::new(DownloadList {
+                        download_list: files,
+                        client,
+                        concurrent_downloads,
+                        control,
+                    });
+                    (
+                        (id, Progress::StartedJavaDownload(pool.stats())),
+                        State::DownloadingJava(pool, runtime),
+                    )
+                }
+                Err(e) => ((id, Progress::Errored(e)), State::Idle),
+            }
         }
+        State::DownloadingJava(mut pool, runtime) => match pool.next().await {
+            Some(Ok(())) => (
+                (id, Progress::JavaDownloadProgressed(pool.stats())),
+                State::DownloadingJava(pool, runtime),
+            ),
+            Some(Err(e)) => ((id, e.into()), State::Idle),
+            None => match runtime.finish() {
+                Ok(()) => ((id, Progress::JavaInstalled), State::Idle),
+                Err(e) => ((id, Progress::Errored(e)), State::Idle),
+            },
+        },
+        State::DownloadingMissingFiles(mut pool) => match pool.next().await {
+            Some(Ok(())) => (
+                (id, Progress::MissingFilesDownloadProgressed(pool.stats())),
+                State::DownloadingMissingFiles(pool),
+            ),
+            Some(Err(e)) => ((id, e.into()), State::Idle),
+            None => {
+                println!("finished");
+                ((id, Progress::MissingFilesDownloadFinished), State::Idle)
+            }
+        },
     }
 }
 
@@ -520,215 +1209,408 @@ pub async fn downloadversionjson(
     version: &String,
     foldertosave: &String,
     client: &Client,
-) -> Result<Value, reqwest::Error> {
+    endpoints: &Endpoints,
+) -> Result<Value, String> {
     match version_type {
         VersionType::Vanilla => {
-            let versionlistjson = reqwest::Client::new()
-                .get("https://launchermeta.mojang.com/mc/game/version_manifest_v2.json")
-                .send()
-                .await?
-                .text()
-                .await?;
+            download_vanilla_json(version, foldertosave, client, endpoints).await
+        }
+        // their json comes from the installer, see forge::Installer.
+        VersionType::Forge(_) | VersionType::NeoForge(_) => Err(format!(
+            "{} can only be installed through its installer",
+            version_type.version_name(version)
+        )),
+        VersionType::Fabric(loaderversion) | VersionType::Quilt(loaderversion) => {
+            // fabric and quilt versions also need the vanilla json, so we are downloading it too.
+            download_vanilla_json(version, foldertosave, client, endpoints).await?;
+
+            // fabric or quilt json
+            let profile_url = match version_type {
+                VersionType::Quilt(_) => format!(
+                    "https://meta.quiltmc.org/v3/versions/loader/{}/{}/profile/json",
+                    version, loaderversion
+                ),
+                _ => format!(
+                    "https://meta.fabricmc.net/v2/versions/loader/{}/{}/profile/json",
+                    version, loaderversion
+                ),
+            };
+            let jfilelocation = format!(
+                "{}/{}.json",
+                foldertosave,
+                version_type.version_name(version)
+            );
+            save_json(
+                client,
+                &endpoints.resolve(&profile_url),
+                foldertosave,
+                &jfilelocation,
+            )
+            .await
+        }
+    }
+}
 
-            let content = serde_json::from_str(&versionlistjson);
+async fn download_vanilla_json(
+    version: &String,
+    foldertosave: &String,
+    client: &Client,
+    endpoints: &Endpoints,
+) -> Result<Value, String> {
+    let manifest_url = endpoints.version_manifest();
+    let mut p = get_cached_manifest(client, &manifest_url, "version_manifest_v2").await?;
+
+    // a cached manifest can be older than the version we are looking for.
+    if get_version_url(&p, version).is_none() {
+        p = refresh_manifest(client, &manifest_url, "version_manifest_v2").await?;
+    }
 
-            let p: Value = content.unwrap();
+    let url = match get_version_url(&p, version) {
+        Some(url) => url,
+        None => return Err(format!("{} was not found in the version manifest", version)),
+    };
 
-            let mut url = "";
+    println!("Downloading json...");
+    let jfilelocation = format!("{}/{}.json", foldertosave, version);
+    save_json(
+        client,
+        &endpoints.resolve(&url),
+        foldertosave,
+        &jfilelocation,
+    )
+    .await
+}
 
-            if let Some(versions) = p["versions"].as_array() {
-                for i in versions {
-                    if i["id"].as_str().unwrap() == version {
-                        url = i["url"].as_str().unwrap();
-                        break;
-                    }
-                }
-            }
-            println!("Downloading json...");
-            let versionjson = reqwest::Client::new()
-                .get(url)
-                .send()
-                .await?
-                .bytes()
-                .await?;
-
-            let jfilelocation = format!("{}/{}.json", foldertosave, version);
-            fs::create_dir_all(foldertosave).unwrap();
-            let mut jfile = File::create(&jfilelocation).unwrap();
-
-            jfile.write_all(&versionjson).unwrap();
-            drop(jfile);
-
-            let mut jfile = File::open(jfilelocation).unwrap();
-            let mut fcontent = String::new();
-            jfile.read_to_string(&mut fcontent).unwrap();
-            let content = serde_json::from_str(&fcontent);
-            let json: Value = content.unwrap();
-            Ok(json)
-        }
-        VersionType::Fabric => {
-            // fabric versions also need the vanilla json, so we are downloading it too.
-
-            // vanilla json
-            let versionlistjson = reqwest::Client::new()
-                .get("https://launchermeta.mojang.com/mc/game/version_manifest_v2.json")
-                .send()
-                .await?
-                .text()
-                .await?;
-
-            let content = serde_json::from_str(&versionlistjson);
-
-            let p: Value = content.unwrap();
-
-            let mut url = "";
-
-            if let Some(versions) = p["versions"].as_array() {
-                for i in versions {
-                    if i["id"].as_str().unwrap() == version {
-                        url = i["url"].as_str().unwrap();
-                        break;
-                    }
+// Downloads a version json into its folder. It is only saved once it was read, since a version
+// with a json counts as installed.
+async fn save_json(client: &Client, url: &str, folder: &str, path: &str) -> Result<Value, String> {
+    let response = match client
+        .get(url)
+        .timeout(super::network::read_timeout())
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+    {
+        Ok(ok) => ok,
+        Err(e) => return Err(e.to_string()),
+    };
+    let content = match response.bytes().await {
+        Ok(ok) => ok,
+        Err(e) => return Err(e.to_string()),
+    };
+    let json: Value = match serde_json::from_slice(&content) {
+        Ok(ok) => ok,
+        Err(e) => return Err(format!("{} is not a valid json: {}", url, e)),
+    };
+
+    if let Err(e) = fs::create_dir_all(folder) {
+        return Err(format!("{}: {}", folder, e));
+    }
+    if let Err(e) = write_file_atomically(path, &content) {
+        return Err(format!("{}: {}", path, e));
+    }
+    Ok(json)
+}
+
+fn get_version_url(manifest: &Value, version: &str) -> Option<String> {
+    manifest["versions"]
+        .as_array()?
+        .iter()
+        .find(|i| i["id"] == version)
+        .and_then(|i| i["url"].as_str())
+        .map(|url| url.to_string())
+}
+
+// Manifests like mojang's version list are kept in siglauncher_cache. A copy younger than this is
+// used without asking the server at all.
+const MANIFEST_CACHE_TTL: Duration = Duration::from_secs(10 * 60);
+
+// Gets a manifest through its cached copy. An outdated copy is still returned straight away and
+// refreshed in the background, and when the server can't be reached any copy is better than none.
+// The manifest is only waited for when it was never downloaded before.
+pub async fn get_cached_manifest(client: &Client, url: &str, name: &str) -> Result<Value, String> {
+    let (json_path, _) = get_manifest_cache_paths(name);
+
+    let cached = fs::read_to_string(&json_path)
+        .ok()
+        .and_then(|content| serde_json::from_str::<Value>(&content).ok());
+
+    let age = fs::metadata(&json_path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok());
+
+    match (cached, age) {
+        (Some(cached), Some(age)) if age < MANIFEST_CACHE_TTL => Ok(cached),
+        (Some(cached), _) => {
+            let (client, url, name) = (client.clone(), url.to_string(), name.to_string());
+            tokio::spawn(async move {
+                if let Err(e) = refresh_manifest(&client, &url, &name).await {
+                    println!("Failed to refresh {name}, using the cached one. -> {e}")
                 }
-            }
+            });
+            Ok(cached)
+        }
+        (None, _) => refresh_manifest(client, url, name).await,
+    }
+}
 
-            println!("Downloading json...");
-            let versionjson = reqwest::Client::new()
-                .get(url)
-                .send()
-                .await?
-                .bytes()
-                .await?;
+// Downloads a manifest again, sending the ETag of the cached copy so the server can answer that
+// nothing changed. Falls back to the cached copy when the server can't be reached.
+pub async fn refresh_manifest(client: &Client, url: &str, name: &str) -> Result<Value, String> {
+    let (json_path, etag_path) = get_manifest_cache_paths(name);
+    let cached = fs::read_to_string(&json_path).ok();
 
-            let jfilelocation = format!("{}/{}.json", foldertosave, version);
-            fs::create_dir_all(foldertosave).unwrap();
-            let mut jfile = File::create(jfilelocation).unwrap();
+    let mut request = client.get(url).timeout(super::network::read_timeout());
+    if let (Some(_), Ok(etag)) = (&cached, fs::read_to_string(&etag_path)) {
+        request = request.header(header::IF_NONE_MATCH, etag);
+    }
 
-            jfile.write_all(&versionjson).unwrap();
+    let response = match request.send().await.and_then(|r| r.error_for_status()) {
+        Ok(ok) => ok,
+        Err(e) => {
+            return match cached {
+                Some(cached) => {
+                    println!("Failed to download {name}, using the cached one. -> {e}");
+                    serde_json::from_str(&cached).map_err(|e| e.to_string())
+                }
+                None => Err(format!("failed to download {}: {}", name, e)),
+            }
+        }
+    };
 
-            // fabric json
-            let fabricloaderlist = client
-                .get("https://meta.fabricmc.net/v2/versions/loader")
-                .send()
-                .await?
-                .text()
-                .await?;
+    if response.status() == StatusCode::NOT_MODIFIED {
+        if let Some(cached) = cached {
+            // marks the cached copy as fresh again.
+            if let Ok(file) = OpenOptions::new().write(true).open(&json_path) {
+                file.set_modified(SystemTime::now()).ok();
+            }
+            return serde_json::from_str(&cached).map_err(|e| e.to_string());
+        }
+    }
 
-            let content: Value = serde_json::from_str(&fabricloaderlist).unwrap();
+    let etag = response
+        .headers()
+        .get(header::ETAG)
+        .and_then(|etag| etag.to_str().ok())
+        .map(|etag| etag.to_string());
 
-            let fabricloaderversion =
-                if let Some(first_object) = content.as_array().and_then(|arr| arr.first()) {
-                    first_object["version"].as_str().unwrap()
-                } else {
-                    panic!("Failed to get fabric loader name")
-                };
+    let content = match response.text().await {
+        Ok(ok) => ok,
+        Err(e) => return Err(format!("failed to download {}: {}", name, e)),
+    };
+    let json: Value = match serde_json::from_str(&content) {
+        Ok(ok) => ok,
+        Err(e) => return Err(format!("failed to read {} as json: {}", name, e)),
+    };
 
-            let verjson = client
-                .get(format!(
-                    "https://meta.fabricmc.net/v2/versions/loader/{}/{}/profile/json",
-                    version, fabricloaderversion
-                ))
-                .send()
-                .await?
-                .bytes()
-                .await?;
+    if let Some(parent) = Path::new(&json_path).parent() {
+        fs::create_dir_all(parent).ok();
+    }
+    if let Err(e) = write_file_atomically(&json_path, content.as_bytes()) {
+        println!("Failed to cache {name}, ignoring. -> {e}");
+    }
+    match etag {
+        Some(etag) => fs::write(&etag_path, etag).ok(),
+        None => fs::remove_file(&etag_path).ok(),
+    };
+
+    Ok(json)
+}
 
-            let jfilelocation = format!("{}/{}-fabric.json", foldertosave, version);
-            fs::create_dir_all(foldertosave).unwrap();
-            let mut jfile = File::create(&jfilelocation).unwrap();
+fn get_manifest_cache_paths(name: &str) -> (String, String) {
+    let cache_dir = format!("{}/siglauncher_cache", super::launcher::get_minecraft_dir());
+    (
+        format!("{}/{}.json", cache_dir, name),
+        format!("{}/{}.etag", cache_dir, name),
+    )
+}
 
-            jfile.write_all(&verjson).unwrap();
-            let mut jfile = File::open(jfilelocation).unwrap();
+// A version from mojang's manifest, with the loaders fabric and quilt have for it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameVersion {
+    pub id: String,
+    // release, snapshot, old_beta or old_alpha.
+    pub version_type: String,
+    pub release_time: String,
+    pub url: String,
+    pub fabric: bool,
+    pub quilt: bool,
+}
 
-            let mut fcontent = String::new();
-            jfile.read_to_string(&mut fcontent).unwrap();
-            let content = serde_json::from_str(&fcontent);
-            let p: Value = content.unwrap();
-            Ok(p)
-        }
+impl GameVersion {
+    pub fn release_date(&self) -> &str {
+        self.release_time.get(..10).unwrap_or(&self.release_time)
     }
 }
 
+// Every version mojang has, newest first.
 pub async fn get_downloadable_version_list(
-    showallversions: bool,
-) -> Result<Vec<Vec<String>>, String> {
-    let client = reqwest::Client::new();
+    endpoints: Endpoints,
+) -> Result<Vec<GameVersion>, String> {
+    let client = super::network::client();
     // vanilla
-    let vanillaversionlistjson = match client
-        .get("https://launchermeta.mojang.com/mc/game/version_manifest_v2.json")
-        .send()
-        .await
+    let p = match get_cached_manifest(
+        &client,
+        &endpoints.version_manifest(),
+        "version_manifest_v2",
+    )
+    .await
     {
-        Ok(ok) => match ok.text().await {
-            Ok(ok) => ok,
-            Err(e) => return Err(format!("failed to get download list: {}", e)),
-        },
+        Ok(ok) => ok,
         Err(e) => return Err(format!("failed to get download list: {}", e)),
     };
-
-    let content = serde_json::from_str(&vanillaversionlistjson);
-
-    let p: Value = match content {
+    // fabric
+    let fabric = match get_cached_manifest(
+        &client,
+        &endpoints.resolve("https://meta.fabricmc.net/v2/versions/game"),
+        "fabric_game_versions",
+    )
+    .await
+    {
+        Ok(ok) => ok,
+        Err(e) => return Err(format!("failed to get fabric download list: {}", e)),
+    };
+    // quilt
+    let quilt = match get_cached_manifest(
+        &client,
+        &endpoints.resolve("https://meta.quiltmc.org/v3/versions/game"),
+        "quilt_game_versions",
+    )
+    .await
+    {
         Ok(ok) => ok,
-        Err(e) => return Err(format!("failed to read list as json: {}", e)),
+        Err(e) => return Err(format!("failed to get quilt download list: {}", e)),
+    };
+    let has_loader = |list: &Value, id: &str| {
+        list.as_array()
+            .into_iter()
+            .flatten()
+            .any(|i| i["version"] == id)
     };
 
-    let mut vanillaversionlist: Vec<String> = vec![];
-    if let Some(versions) = p["versions"].as_array() {
-        if showallversions {
-            for i in versions {
-                vanillaversionlist.push(i["id"].as_str().unwrap().to_owned())
-            }
-        } else {
-            for i in versions {
-                if i["type"] == "release" {
-                    vanillaversionlist.push(i["id"].as_str().unwrap().to_owned())
-                }
-            }
-        }
+    let mut versions = vec![];
+    for i in p["versions"].as_array().into_iter().flatten() {
+        let id = i["id"].as_str().unwrap_or("").to_owned();
+        versions.push(GameVersion {
+            fabric: has_loader(&fabric, &id),
+            quilt: has_loader(&quilt, &id),
+            id,
+            version_type: i["type"].as_str().unwrap_or("").to_owned(),
+            release_time: i["releaseTime"].as_str().unwrap_or("").to_owned(),
+            url: i["url"].as_str().unwrap_or("").to_owned(),
+        })
     }
-    // fabric
-    let fabricversionlistjson = match client
-        .get("https://meta.fabricmc.net/v2/versions/game")
+    // release times all use the same format and timezone, so they sort as text.
+    versions.sort_by(|a, b| b.release_time.cmp(&a.release_time));
+    Ok(versions)
+}
+
+// Java major version a game version needs, from its json.
+pub async fn get_required_java_version(url: String, endpoints: Endpoints) -> Result<i64, String> {
+    let response = match super::network::client()
+        .get(endpoints.resolve(&url))
+        .timeout(super::network::read_timeout())
         .send()
         .await
+        .and_then(|r| r.error_for_status())
     {
-        Ok(ok) => match ok.text().await {
-            Ok(ok) => ok,
-            Err(e) => return Err(format!("failed to get fabric download list: {}", e)),
-        },
-        Err(e) => return Err(format!("failed to get fabric download list: {}", e)),
+        Ok(ok) => ok,
+        Err(e) => return Err(e.to_string()),
     };
+    let content = match response.text().await {
+        Ok(ok) => ok,
+        Err(e) => return Err(e.to_string()),
+    };
+    match serde_json::from_str::<Value>(&content) {
+        Ok(version_json) => Ok(super::java_runtime::get_required_java(&version_json).1),
+        Err(e) => Err(e.to_string()),
+    }
+}
 
-    let content = serde_json::from_str(&fabricversionlistjson);
-
-    let p: Value = match content {
+// Fabric loader versions, newest first.
+pub async fn get_fabric_loader_versions(
+    showallversions: bool,
+    endpoints: Endpoints,
+) -> Result<Vec<LoaderVersion>, String> {
+    let p = match get_cached_manifest(
+        &super::network::client(),
+        &endpoints.resolve("https://meta.fabricmc.net/v2/versions/loader"),
+        "fabric_loader_versions",
+    )
+    .await
+    {
         Ok(ok) => ok,
-        Err(e) => return Err(format!("failed to read fabric list as json: {}", e)),
+        Err(e) => return Err(format!("failed to get fabric loader list: {}", e)),
     };
 
-    let mut fabricversionlist: Vec<String> = vec![];
+    let mut loaderversionlist = vec![];
     if let Some(versions) = p.as_array() {
-        if showallversions {
-            for i in versions {
-                fabricversionlist.push(i["version"].as_str().unwrap().to_owned())
+        for i in versions {
+            let loader = LoaderVersion {
+                version: i["version"].as_str().unwrap().to_owned(),
+                stable: i["stable"] == true,
+            };
+            if showallversions || loader.stable {
+                loaderversionlist.push(loader)
             }
-        } else {
-            for i in versions {
-                if i["stable"] == true {
-                    fabricversionlist.push(i["version"].as_str().unwrap().to_owned())
-                }
+        }
+    }
+    Ok(loaderversionlist)
+}
+
+// Quilt loader versions, newest first.
+pub async fn get_quilt_loader_versions(
+    showallversions: bool,
+    endpoints: Endpoints,
+) -> Result<Vec<LoaderVersion>, String> {
+    let p = match get_cached_manifest(
+        &super::network::client(),
+        &endpoints.resolve("https://meta.quiltmc.org/v3/versions/loader"),
+        "quilt_loader_versions",
+    )
+    .await
+    {
+        Ok(ok) => ok,
+        Err(e) => return Err(format!("failed to get quilt loader list: {}", e)),
+    };
+
+    let mut loaderversionlist = vec![];
+    if let Some(versions) = p.as_array() {
+        for i in versions {
+            let version = i["version"].as_str().unwrap().to_owned();
+            // quilt doesn't flag stable loaders, but betas and pre-releases have a suffix.
+            let loader = LoaderVersion {
+                stable: !version.contains('-'),
+                version,
+            };
+            if showallversions || loader.stable {
+                loaderversionlist.push(loader)
             }
         }
     }
-    Ok(vec![vanillaversionlist, fabricversionlist])
+    Ok(loaderversionlist)
 }
 
+// Libraries and natives of a version json that apply to this system, creating their folders.
 pub fn get_libraries(
     mc_dir: &String,
-    libraries: &Vec<Value>,
+    libraries: &[Value],
+    foldertosave: &String,
+    endpoints: &Endpoints,
+) -> Result<Vec<Download>, String> {
+    let library_download_list = get_library_downloads(mc_dir, libraries, foldertosave, endpoints)?;
+    create_download_folders(&library_download_list)?;
+    Ok(library_download_list)
+}
+
+// Same as get_libraries, without touching the disk.
+pub fn get_library_downloads(
+    mc_dir: &String,
+    libraries: &[Value],
     foldertosave: &String,
-) -> Result<Vec<Download>, Box<dyn std::error::Error>> {
+    endpoints: &Endpoints,
+) -> Result<Vec<Download>, String> {
     //libraries and natives
     let lib_dir = format!("{}/libraries/", mc_dir);
     let os = std::env::consts::OS;
@@ -741,230 +1623,450 @@ pub fn get_libraries(
 
     let mut library_download_list = vec![];
 
-    for library in libraries {
-        if library["rules"][0]["os"]["name"] == os || library["rules"][0]["os"]["name"].is_null() {
-            let libraryname = library["name"].as_str().unwrap();
-            let mut lpieces: Vec<&str> = libraryname.split(':').collect();
-            let firstpiece = lpieces.remove(0).replace('.', "/");
-
-            let libtype = if library["name"]
-                .as_str()
-                .unwrap()
-                .contains(&format!("natives-{}", os))
-            {
-                LibraryType::Natives
-            } else if library["natives"][os].is_null() {
-                LibraryType::Normal
-            } else {
-                LibraryType::Old
-            };
-
-            match libtype {
-                LibraryType::Natives => {
-                    let last_piece = lpieces.pop().unwrap();
-                    let lib = format!(
-                        "{}/{}/{}-{}-{}.jar",
-                        &firstpiece,
-                        &lpieces.join("/"),
-                        &lpieces[&lpieces.len() - 2],
-                        &lpieces[&lpieces.len() - 1],
-                        last_piece
-                    );
-
-                    // create folder for lib
-                    match fs::create_dir_all(format!(
-                        "{}/{}/{}",
-                        lib_dir,
-                        &firstpiece,
-                        &lpieces.join("/")
-                    )) {
-                        Ok(ok) => ok,
-                        Err(err) => panic!("{err}"),
-                    };
-
-                    let libpath = format!("{}{}", lib_dir, lib);
-
-                    let unmodifiedurl = if !library["downloads"]["artifact"]["url"].is_null() {
-                        library["downloads"]["artifact"]["url"].as_str().unwrap()
-                    } else if !library["url"].is_null() {
-                        library["url"].as_str().unwrap()
-                    } else {
-                        ""
-                    };
-
-                    let url = get_library_url(unmodifiedurl, lib);
+    for library in &super::rules::get_system_libraries(libraries) {
+        let natives_classifier = super::rules::get_natives_classifier(library)?;
+        let libraryname = library["name"].as_str().unwrap();
+        let mut lpieces: Vec<&str> = libraryname.split(':').collect();
+        let firstpiece = lpieces.remove(0).replace('.', "/");
+
+        let libtype = if library["name"]
+            .as_str()
+            .unwrap()
+            .contains(&format!("natives-{}", os))
+        {
+            LibraryType::Natives
+        } else if library["natives"][os].is_null() {
+            LibraryType::Normal
+        } else {
+            LibraryType::Old
+        };
+
+        match libtype {
+            LibraryType::Natives => {
+                let last_piece = lpieces.pop().unwrap();
+                let lib = format!(
+                    "{}/{}/{}-{}-{}.jar",
+                    &firstpiece,
+                    &lpieces.join("/"),
+                    &lpieces[&lpieces.len() - 2],
+                    &lpieces[&lpieces.len() - 1],
+                    last_piece
+                );
 
-                    library_download_list.push(Download { path: libpath, url })
-                }
+                let libpath = format!("{}{}", lib_dir, lib);
 
-                LibraryType::Normal => {
-                    let lib = format!(
-                        "{}/{}/{}-{}.jar",
-                        &firstpiece,
-                        &lpieces.join("/"),
-                        &lpieces[&lpieces.len() - 2],
-                        &lpieces[&lpieces.len() - 1]
-                    );
+                let unmodifiedurl = if !library["downloads"]["artifact"]["url"].is_null() {
+                    library["downloads"]["artifact"]["url"].as_str().unwrap()
+                } else if !library["url"].is_null() {
+                    library["url"].as_str().unwrap()
+                } else {
+                    ""
+                };
 
-                    // create folder for lib
-                    match fs::create_dir_all(format!(
-                        "{}/{}/{}",
-                        lib_dir,
-                        &firstpiece,
-                        &lpieces.join("/")
-                    )) {
-                        Ok(ok) => ok,
-                        Err(err) => panic!("{err}"),
-                    };
+                let url = get_library_url(unmodifiedurl, lib, endpoints);
+                let (sha1, size) = get_library_checksum(library);
 
-                    let libpath = format!("{}{}", lib_dir, lib);
+                library_download_list.push(Download {
+                    path: libpath,
+                    url,
+                    sha1,
+                    size,
+                    extract: None,
+                })
+            }
 
-                    let unmodifiedurl = if !library["downloads"]["artifact"]["url"].is_null() {
-                        library["downloads"]["artifact"]["url"].as_str().unwrap()
-                    } else if !library["url"].is_null() {
-                        library["url"].as_str().unwrap()
-                    } else {
-                        ""
-                    };
+            LibraryType::Normal => {
+                let lib = get_library_path(libraryname);
+                let libpath = format!("{}{}", lib_dir, lib);
 
-                    let url = get_library_url(unmodifiedurl, lib);
+                let unmodifiedurl = if !library["downloads"]["artifact"]["url"].is_null() {
+                    library["downloads"]["artifact"]["url"].as_str().unwrap()
+                } else if !library["url"].is_null() {
+                    library["url"].as_str().unwrap()
+                } else {
+                    ""
+                };
 
-                    library_download_list.push(Download { path: libpath, url })
-                }
+                let url = get_library_url(unmodifiedurl, lib, endpoints);
+                let (sha1, size) = get_library_checksum(library);
 
-                LibraryType::Old => {
-                    let lib = format!(
-                        "{}/{}/{}-{}-natives-{}.jar",
-                        &firstpiece,
-                        &lpieces.join("/"),
-                        &lpieces[&lpieces.len() - 2],
-                        &lpieces[&lpieces.len() - 1],
-                        os
-                    );
+                library_download_list.push(Download {
+                    path: libpath,
+                    url,
+                    sha1,
+                    size,
+                    extract: None,
+                })
+            }
 
-                    // create folder for lib
-                    match fs::create_dir_all(format!(
-                        "{}/{}/{}",
-                        lib_dir,
-                        &firstpiece,
-                        &lpieces.join("/")
-                    )) {
-                        Ok(ok) => ok,
-                        Err(err) => panic!("{err}"),
-                    };
+            LibraryType::Old => {
+                let lib = format!(
+                    "{}/{}/{}-{}-natives-{}.jar",
+                    &firstpiece,
+                    &lpieces.join("/"),
+                    &lpieces[&lpieces.len() - 2],
+                    &lpieces[&lpieces.len() - 1],
+                    os
+                );
 
-                    let libpath = format!("{}{}", lib_dir, lib);
+                let libpath = format!("{}{}", lib_dir, lib);
 
-                    let unmodifiedurl = if !library["downloads"]["artifact"]["url"].is_null() {
-                        library["downloads"]["artifact"]["url"].as_str().unwrap()
-                    } else if !library["url"].is_null() {
-                        library["url"].as_str().unwrap()
-                    } else if !library["downloads"]["classifiers"][format!("natives-{}", os)]["url"]
-                        .is_null()
-                        || library["downloads"]["classifiers"][format!("natives-{}-64", os)]["url"]
-                            .is_string()
-                    {
-                        let url = if !library["downloads"]["classifiers"][format!("natives-{}", os)]
-                            ["url"]
-                            .is_null()
-                        {
-                            library["downloads"]["classifiers"][format!("natives-{}", os)]["url"]
-                                .as_str()
-                                .unwrap()
-                        } else {
-                            library["downloads"]["classifiers"][format!("natives-{}-64", os)]["url"]
-                                .as_str()
-                                .unwrap()
-                        };
+                let classifier = &library["downloads"]["classifiers"]
+                    [natives_classifier.clone().unwrap_or_default()];
+                let unmodifiedurl = if !library["downloads"]["artifact"]["url"].is_null() {
+                    library["downloads"]["artifact"]["url"].as_str().unwrap()
+                } else if !library["url"].is_null() {
+                    library["url"].as_str().unwrap()
+                } else {
+                    classifier["url"].as_str().unwrap_or("")
+                };
 
-                        url
-                    } else {
-                        ""
-                    };
+                let (sha1, size) = if !library["downloads"]["artifact"]["url"].is_null()
+                    || !library["url"].is_null()
+                {
+                    get_library_checksum(library)
+                } else {
+                    get_checksum(classifier)
+                };
 
-                    let url = get_library_url(unmodifiedurl, lib);
+                let url = get_library_url(unmodifiedurl, lib, endpoints);
 
-                    library_download_list.push(Download { path: libpath, url })
-                }
+                library_download_list.push(Download {
+                    path: libpath,
+                    url,
+                    sha1,
+                    size,
+                    extract: None,
+                })
             }
         }
 
-        if !library["downloads"]["classifiers"][format!("natives-{}", os)].is_null() {
-            let url = library["downloads"]["classifiers"][format!("natives-{}", os)]["url"]
-                .as_str()
-                .unwrap()
-                .to_string();
-
-            fs::create_dir_all(format!("{}/natives", foldertosave)).unwrap();
-            let path = format!("{}/natives/natives.jar", foldertosave);
+        let natives = match natives_classifier {
+            Some(classifier) => &library["downloads"]["classifiers"][classifier],
+            None => &Value::Null,
+        };
+        if let Some(url) = natives["url"].as_str() {
+            let url = endpoints.resolve(url);
+
+            // every natives jar gets its own name, so they can be downloaded at the same time.
+            let path = format!(
+                "{}/natives/{}-natives.jar",
+                foldertosave,
+                library["name"].as_str().unwrap().replace(':', "-")
+            );
 
-            library_download_list.push(Download { path, url });
+            let (sha1, size) = get_checksum(natives);
+
+            let exclude = library["extract"]["exclude"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|entry| entry.as_str().map(|entry| entry.to_string()))
+                .collect();
+
+            library_download_list.push(Download {
+                path,
+                url,
+                sha1,
+                size,
+                extract: Some(exclude),
+            });
         }
     }
     Ok(library_download_list)
 }
 
-fn get_library_url(unmodifiedurl: &str, lib: String) -> String {
-    match unmodifiedurl {
-        "https://maven.fabricmc.net/" => format!("{}{}", unmodifiedurl, lib),
-        "https://maven.minecraftforge.net/" => format!("{}{}", unmodifiedurl, lib),
+// Path of a library inside of the libraries folder, from its maven name
+// (group:name:version, with an optional :classifier and @extension).
+pub fn get_library_path(name: &str) -> String {
+    let (name, extension) = name.split_once('@').unwrap_or((name, "jar"));
+    let pieces: Vec<&str> = name.split(':').collect();
+    if pieces.len() < 3 {
+        return name.to_string();
+    }
+
+    let file_name = match pieces.get(3) {
+        Some(classifier) => format!("{}-{}-{}.{}", pieces[1], pieces[2], classifier, extension),
+        None => format!("{}-{}.{}", pieces[1], pieces[2], extension),
+    };
+    format!(
+        "{}/{}/{}/{}",
+        pieces[0].replace('.', "/"),
+        pieces[1],
+        pieces[2],
+        file_name
+    )
+}
+
+fn get_library_checksum(library: &Value) -> (Option<String>, Option<u64>) {
+    if library["downloads"]["artifact"].is_object() {
+        get_checksum(&library["downloads"]["artifact"])
+    } else {
+        // fabric libraries keep them next to the library name.
+        get_checksum(library)
+    }
+}
+
+fn get_library_url(unmodifiedurl: &str, lib: String, endpoints: &Endpoints) -> String {
+    let url = match unmodifiedurl {
         "" => format!("https://libraries.minecraft.net/{}", lib),
+        // maven repositories, like fabric's and quilt's, only give the repository url.
+        _ if unmodifiedurl.ends_with('/') => format!("{}{}", unmodifiedurl, lib),
         _ => unmodifiedurl.to_string(),
+    };
+    endpoints.resolve(&url)
+}
+
+// Where the log4j configuration of a version json is stored, if it has one.
+pub fn get_log_config_path(mc_dir: &str, version_json: &Value) -> Option<String> {
+    let id = version_json["logging"]["client"]["file"]["id"].as_str()?;
+    // the id is used as a file name.
+    if id.contains(['/', '\\']) || id.starts_with('.') {
+        return None;
     }
+    Some(format!("{}/assets/log_configs/{}", mc_dir, id))
 }
 
-pub fn get_assets(mc_dir: &String, asset_index_json: Value) -> Result<Vec<Download>, String> {
-    let save_to_resources = !asset_index_json["map_to_resources"].is_null();
+pub fn get_log_config(
+    mc_dir: &str,
+    version_json: &Value,
+    endpoints: &Endpoints,
+) -> Result<Option<Download>, String> {
+    let path = match get_log_config_path(mc_dir, version_json) {
+        Some(path) => path,
+        None => return Ok(None),
+    };
+    if let Err(e) = fs::create_dir_all(format!("{}/assets/log_configs", mc_dir)) {
+        return Err(e.to_string());
+    }
+
+    let file = &version_json["logging"]["client"]["file"];
+    let (sha1, size) = get_checksum(file);
+    Ok(Some(Download {
+        path,
+        url: endpoints.resolve(file["url"].as_str().unwrap_or("")),
+        sha1,
+        size,
+        extract: None,
+    }))
+}
+
+// The asset index of a version json, which lists every asset the version needs.
+pub fn get_asset_index(
+    mc_dir: &str,
+    version_json: &Value,
+    endpoints: &Endpoints,
+) -> Result<Download, String> {
+    let (index, url) = match (
+        version_json["assets"].as_str(),
+        version_json["assetIndex"]["url"].as_str(),
+    ) {
+        (Some(index), Some(url)) => (index, url),
+        _ => return Err(String::from("the version json has no asset index")),
+    };
+    if let Err(e) = fs::create_dir_all(format!("{}/assets/indexes", mc_dir)) {
+        return Err(e.to_string());
+    }
+
+    let (sha1, size) = get_checksum(&version_json["assetIndex"]);
+    Ok(Download {
+        path: format!("{}/assets/indexes/{}.json", mc_dir, index),
+        url: endpoints.resolve(url),
+        sha1,
+        size,
+        extract: None,
+    })
+}
+
+// Assets listed in an asset index, creating their folders.
+pub fn get_assets(
+    mc_dir: &String,
+    asset_index_json: Value,
+    endpoints: &Endpoints,
+) -> Result<Vec<Download>, String> {
+    let download_list = get_asset_downloads(mc_dir, &asset_index_json, endpoints);
+    create_download_folders(&download_list)?;
+    Ok(download_list)
+}
+
+// Same as get_assets, without touching the disk.
+pub fn get_asset_downloads(
+    mc_dir: &String,
+    asset_index_json: &Value,
+    endpoints: &Endpoints,
+) -> Vec<Download> {
+    // assets are always stored by hash, the launcher copies them where old versions look for
+    // them before launching, see launcher::get_game_assets.
     let mut download_list = Vec::new();
 
     if let Some(assets) = asset_index_json["objects"].as_object() {
         let assets_directory = format!("{}/assets/objects/", &mc_dir);
-        let old_assets_directory = format!("{}/resources", &mc_dir);
 
-        for (key, value) in assets.iter() {
+        for value in assets.values() {
             if let Some(hash) = value["hash"].as_str() {
-                match save_to_resources {
-                    true => {
-                        match fs::create_dir_all(&old_assets_directory) {
-                            Ok(ok) => ok,
-                            Err(e) => return Err(e.to_string()),
-                        };
+                let asset_path = format!("{}/{}/{}", &assets_directory, &hash[0..2], &hash);
+
+                let asset_url = endpoints.resolve(&format!(
+                    "https://resources.download.minecraft.net/{}/{}",
+                    &hash[0..2],
+                    hash
+                ));
+
+                download_list.push(Download {
+                    path: asset_path,
+                    url: asset_url,
+                    sha1: Some(hash.to_string()),
+                    size: value["size"].as_u64(),
+                    extract: None,
+                });
+            }
+        }
+    }
 
-                        let asset_path = format!("{}/{}", old_assets_directory, key);
-                        let asset_url = format!(
-                            "https://resources.download.minecraft.net/{}/{}",
-                            &hash[0..2],
-                            hash
-                        );
-
-                        download_list.push(Download {
-                            path: asset_path,
-                            url: asset_url,
-                        });
-                    }
+    download_list
+}
 
-                    false => {
-                        match fs::create_dir_all(format!("{}/{}", assets_directory, &hash[0..2])) {
-                            Ok(ok) => ok,
-                            Err(e) => return Err(e.to_string()),
-                        };
-                        let asset_path = format!("{}/{}/{}", &assets_directory, &hash[0..2], &hash);
-
-                        let asset_url = format!(
-                            "https://resources.download.minecraft.net/{}/{}",
-                            &hash[0..2],
-                            hash
-                        );
-
-                        download_list.push(Download {
-                            path: asset_path,
-                            url: asset_url,
-                        });
-                    }
-                }
-            }
+fn create_download_folders(downloads: &[Download]) -> Result<(), String> {
+    let folders: HashSet<&Path> = downloads
+        .iter()
+        .filter_map(|download| Path::new(&download.path).parent())
+        .collect();
+    for folder in folders {
+        if let Err(e) = fs::create_dir_all(folder) {
+            return Err(format!("{}: {}", folder.display(), e));
         }
     }
+    Ok(())
+}
 
-    Ok(download_list)
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{io::Cursor, path::PathBuf};
+    use zip::{write::FileOptions, ZipWriter};
+
+    // Writes a zip with the given entries to a new folder, returning the folder and the zip path.
+    fn write_archive(name: &str, entries: &[(&str, &[u8])]) -> (PathBuf, String) {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        for (entry, content) in entries {
+            writer.start_file(*entry, FileOptions::default()).unwrap();
+            writer.write_all(content).unwrap();
+        }
+        let archive = writer.finish().unwrap().into_inner();
+
+        let folder =
+            std::env::temp_dir().join(format!("siglauncher-test-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&folder);
+        fs::create_dir_all(folder.join("natives")).unwrap();
+        let archive_path = folder.join("natives.jar").to_string_lossy().to_string();
+        fs::write(&archive_path, archive).unwrap();
+        (folder, archive_path)
+    }
+
+    #[test]
+    fn extract_archive_skips_excluded_entries() {
+        let (folder, archive_path) = write_archive(
+            "exclude",
+            &[
+                ("liblwjgl.so", b"native"),
+                ("META-INF/MANIFEST.MF", b"manifest"),
+                ("META-INF/signature.sha1", b"signature"),
+            ],
+        );
+        let natives = folder.join("natives");
+
+        extract_archive(&archive_path, &natives, &[String::from("META-INF/")]).unwrap();
+
+        assert_eq!(fs::read(natives.join("liblwjgl.so")).unwrap(), b"native");
+        assert!(!natives.join("META-INF").exists());
+        fs::remove_dir_all(folder).unwrap();
+    }
+
+    #[test]
+    fn extract_archive_rejects_entries_outside_of_the_folder() {
+        let (folder, archive_path) = write_archive(
+            "zip-slip",
+            &[("liblwjgl.so", b"native"), ("../escaped.so", b"evil")],
+        );
+        let natives = folder.join("natives");
+
+        let result = extract_archive(&archive_path, &natives, &[]);
+
+        assert!(result.is_err());
+        assert!(!folder.join("escaped.so").exists());
+        fs::remove_dir_all(folder).unwrap();
+    }
+
+    #[test]
+    fn check_extracted_finds_changed_files() {
+        let (folder, archive_path) = write_archive(
+            "check",
+            &[
+                ("liblwjgl.so", b"native"),
+                ("META-INF/MANIFEST.MF", b"manifest"),
+            ],
+        );
+        let natives = folder.join("natives");
+        let exclude = [String::from("META-INF/")];
+        extract_archive(&archive_path, &natives, &exclude).unwrap();
+
+        assert_eq!(check_extracted(&archive_path, &natives, &exclude), None);
+        fs::write(natives.join("liblwjgl.so"), b"corrupted").unwrap();
+        assert!(check_extracted(&archive_path, &natives, &exclude).is_some());
+        fs::remove_file(natives.join("liblwjgl.so")).unwrap();
+        assert!(check_extracted(&archive_path, &natives, &exclude).is_some());
+        fs::remove_dir_all(folder).unwrap();
+    }
+
+    #[test]
+    fn extract_archive_rejects_absolute_entries() {
+        let (folder, archive_path) =
+            write_archive("absolute", &[("/tmp/siglauncher-escaped.so", b"evil")]);
+
+        let result = extract_archive(&archive_path, &folder.join("natives"), &[]);
+
+        assert!(result.is_err());
+        fs::remove_dir_all(folder).unwrap();
+    }
+
+    #[test]
+    fn resolve_keeps_urls_with_the_default_endpoints() {
+        let url = "https://libraries.minecraft.net/org/lwjgl/lwjgl/3.3.1/lwjgl-3.3.1.jar";
+        assert_eq!(Endpoints::default().resolve(url), url);
+    }
+
+    #[test]
+    fn resolve_rewrites_official_hosts() {
+        let endpoints = Endpoints {
+            launcher_meta: String::from("http://localhost:8080/meta/"),
+            // the trailing slash is optional.
+            resources: String::from("http://localhost:8080/assets"),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            endpoints.resolve("https://piston-meta.mojang.com/mc/game/version_manifest_v2.json"),
+            "http://localhost:8080/meta/mc/game/version_manifest_v2.json"
+        );
+        // old version jsons still point at the hosts mojang used before.
+        assert_eq!(
+            endpoints.resolve("https://launchermeta.mojang.com/v1/packages/abc/1.8.9.json"),
+            "http://localhost:8080/meta/v1/packages/abc/1.8.9.json"
+        );
+        assert_eq!(
+            endpoints.resolve("https://resources.download.minecraft.net/ab/abcdef"),
+            "http://localhost:8080/assets/ab/abcdef"
+        );
+    }
+
+    #[test]
+    fn resolve_leaves_other_urls_alone() {
+        let endpoints = Endpoints {
+            libraries: String::from("http://localhost:8080/libraries/"),
+            ..Default::default()
+        };
+        let url = "https://example.com/libraries.minecraft.net/lwjgl.jar";
+        assert_eq!(endpoints.resolve(url), url);
+        assert_eq!(endpoints.resolve(""), "");
+    }
 }
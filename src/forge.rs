@@ -0,0 +1,482 @@
+use super::downloader::{
+    get_cached_manifest, get_library_path, write_file_atomically, Endpoints, LoaderVersion,
+    VersionType,
+};
+use super::java_runtime::JavaRuntime;
+use super::launcher::get_classpath_separator;
+use reqwest::Client;
+use serde_json::Value;
+use std::{
+    collections::{HashMap, VecDeque},
+    fs::{self, File},
+    io::{BufReader, Read},
+    path::Path,
+    process::Command,
+};
+use zip::ZipArchive;
+
+// Forge and NeoForge are installed by running their own installer jar: it has the version json,
+// the loader libraries and a list of processors, java programs that patch the vanilla client.
+pub struct Installer {
+    installer_path: String,
+    work_dir: String,
+    lib_dir: String,
+    java: String,
+    data: HashMap<String, String>,
+    processors: VecDeque<Value>,
+    // libraries that still have to be downloaded, from both the version json and the installer.
+    pub libraries: Vec<Value>,
+    // the java runtime the processors run with, when it is downloaded along with the version.
+    pub java_runtime: Option<JavaRuntime>,
+}
+
+pub fn get_installer_url(version_type: &VersionType) -> Option<String> {
+    match version_type {
+        VersionType::Forge(loader) => Some(format!(
+            "https://maven.minecraftforge.net/net/minecraftforge/forge/{}/forge-{}-installer.jar",
+            loader, loader
+        )),
+        VersionType::NeoForge(loader) => Some(format!(
+            "https://maven.neoforged.net/releases/net/neoforged/neoforge/{}/neoforge-{}-installer.jar",
+            loader, loader
+        )),
+        _ => None,
+    }
+}
+
+impl Installer {
+    // Reads the installer, writes the version json to the version folder and copies the libraries
+    // that come inside of the installer to the libraries folder.
+    pub fn new(
+        installer_path: &str,
+        mc_dir: &str,
+        version_folder: &str,
+        version_name: &str,
+        game_version: &str,
+        java: &str,
+    ) -> Result<Installer, String> {
+        let installer = match File::open(installer_path) {
+            Ok(ok) => ok,
+            Err(e) => return Err(format!("failed to open the installer: {}", e)),
+        };
+        let mut archive = match ZipArchive::new(BufReader::new(installer)) {
+            Ok(ok) => ok,
+            Err(e) => return Err(format!("failed to open the installer: {}", e)),
+        };
+
+        let profile: Value =
+            match serde_json::from_str(&read_entry(&mut archive, "install_profile.json")?) {
+                Ok(ok) => ok,
+                Err(e) => return Err(format!("failed to read install_profile.json: {}", e)),
+            };
+
+        let lib_dir = format!("{}/libraries", mc_dir);
+        let work_dir = format!("{}.data", installer_path.trim_end_matches(".jar"));
+
+        // old installers (1.12.2 and older) keep the version json inside of the profile and
+        // only carry the forge jar itself.
+        let mut version_json = if profile["versionInfo"].is_object() {
+            let universal_path = format!(
+                "{}/{}",
+                lib_dir,
+                get_library_path(profile["install"]["path"].as_str().unwrap_or(""))
+            );
+            extract_entry(
+                &mut archive,
+                profile["install"]["filePath"].as_str().unwrap_or(""),
+                &universal_path,
+            )?;
+            profile["versionInfo"].clone()
+        } else {
+            let json_entry = profile["json"].as_str().unwrap_or("/version.json");
+            match serde_json::from_str(&read_entry(&mut archive, json_entry)?) {
+                Ok(ok) => ok,
+                Err(e) => return Err(format!("failed to read {}: {}", json_entry, e)),
+            }
+        };
+
+        // libraries shipped inside of the installer.
+        for i in 0..archive.len() {
+            let mut file = match archive.by_index(i) {
+                Ok(ok) => ok,
+                Err(e) => return Err(e.to_string()),
+            };
+            let path = match file.enclosed_name() {
+                Some(path) => path.to_owned(),
+                None => continue,
+            };
+            if let Ok(library) = path.strip_prefix("maven") {
+                let library_path = Path::new(&lib_dir).join(library);
+                if file.is_file() && !library_path.exists() {
+                    write_entry(&mut file, &library_path.to_string_lossy())?;
+                }
+            }
+        }
+
+        // the files made by the processors have no url, they don't exist until the processors run.
+        let mut libraries = vec![];
+        for library in version_json["libraries"]
+            .as_array()
+            .into_iter()
+            .chain(profile["libraries"].as_array())
+            .flatten()
+        {
+            if library["downloads"]["artifact"]["url"] != "" {
+                libraries.push(library.clone())
+            }
+        }
+
+        let mut data = HashMap::new();
+        if let Some(values) = profile["data"].as_object() {
+            for (key, value) in values {
+                let value = value["client"].as_str().unwrap_or("");
+                let value = if value.starts_with('/') {
+                    // files like the binary patches are read straight from the installer.
+                    let path = format!("{}{}", work_dir, value);
+                    extract_entry(&mut archive, value, &path)?;
+                    path
+                } else {
+                    resolve_value(value, &lib_dir)
+                };
+                data.insert(key.clone(), value);
+            }
+        }
+        data.insert(String::from("SIDE"), String::from("client"));
+        data.insert(
+            String::from("MINECRAFT_JAR"),
+            format!("{}/{}.jar", version_folder, version_name),
+        );
+        data.insert(String::from("MINECRAFT_VERSION"), game_version.to_string());
+        data.insert(String::from("ROOT"), mc_dir.to_string());
+        data.insert(String::from("INSTALLER"), installer_path.to_string());
+        data.insert(String::from("LIBRARY_DIR"), lib_dir.clone());
+
+        let mut processors = VecDeque::new();
+        if let Some(list) = profile["processors"].as_array() {
+            for processor in list {
+                let is_for_client = match processor["sides"].as_array() {
+                    Some(sides) => sides.iter().any(|side| side == "client"),
+                    None => true,
+                };
+                if is_for_client {
+                    processors.push_back(processor.clone())
+                }
+            }
+        }
+
+        // the launcher finds versions by their folder name, and every loader version needs the
+        // vanilla json next to it.
+        version_json["id"] = Value::String(version_name.to_string());
+        if version_json["inheritsFrom"].is_null() {
+            version_json["inheritsFrom"] = Value::String(game_version.to_string());
+        }
+        if let Err(e) = fs::create_dir_all(version_folder) {
+            return Err(e.to_string());
+        }
+        if let Err(e) = write_file_atomically(
+            &format!("{}/{}.json", version_folder, version_name),
+            version_json.to_string().as_bytes(),
+        ) {
+            return Err(e.to_string());
+        }
+
+        Ok(Installer {
+            installer_path: installer_path.to_string(),
+            work_dir,
+            lib_dir,
+            java: java.to_string(),
+            data,
+            processors,
+            libraries,
+            java_runtime: None,
+        })
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.processors.len()
+    }
+
+    // Runs the next processor with the launcher's java. This blocks until the processor exits.
+    pub fn run_next_processor(&mut self) -> Result<(), String> {
+        let processor = match self.processors.pop_front() {
+            Some(processor) => processor,
+            None => return Ok(()),
+        };
+
+        // processors whose files are already there with the right hash can be skipped.
+        let outputs = self.get_outputs(&processor)?;
+        if !outputs.is_empty() && self.check_outputs(&outputs).is_ok() {
+            return Ok(());
+        }
+
+        let jar = format!(
+            "{}/{}",
+            self.lib_dir,
+            get_library_path(processor["jar"].as_str().unwrap_or(""))
+        );
+        let main_class = get_main_class(&jar)?;
+
+        let mut classpath = vec![jar];
+        if let Some(libraries) = processor["classpath"].as_array() {
+            for library in libraries {
+                classpath.push(format!(
+                    "{}/{}",
+                    self.lib_dir,
+                    get_library_path(library.as_str().unwrap_or(""))
+                ));
+            }
+        }
+
+        let mut args = vec![];
+        if let Some(arguments) = processor["args"].as_array() {
+            for arg in arguments {
+                args.push(self.resolve_argument(arg.as_str().unwrap_or(""))?);
+            }
+        }
+
+        println!("Running processor {}", main_class);
+        let output = match Command::new(&self.java)
+            .arg("-cp")
+            .arg(classpath.join(get_classpath_separator()))
+            .arg(&main_class)
+            .args(args)
+            .output()
+        {
+            Ok(ok) => ok,
+            Err(e) => return Err(format!("failed to run {}: {}", self.java, e)),
+        };
+
+        println!("{}", String::from_utf8_lossy(&output.stdout));
+        if !output.status.success() {
+            return Err(format!(
+                "{} exited with {}: {}",
+                main_class,
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+
+        self.check_outputs(&outputs)
+    }
+
+    // Deletes the installer and the files extracted from it.
+    pub fn clean_up(self) {
+        fs::remove_dir_all(&self.work_dir).ok();
+        fs::remove_file(&self.installer_path).ok();
+    }
+
+    fn resolve_argument(&self, arg: &str) -> Result<String, String> {
+        match arg.strip_prefix('{').and_then(|arg| arg.strip_suffix('}')) {
+            Some(key) => match self.data.get(key) {
+                Some(value) => Ok(value.clone()),
+                None => Err(format!("the installer doesn't have a value for {}", key)),
+            },
+            None => Ok(resolve_value(arg, &self.lib_dir)),
+        }
+    }
+
+    // Output files of a processor and their expected sha1.
+    fn get_outputs(&self, processor: &Value) -> Result<Vec<(String, String)>, String> {
+        let mut outputs = vec![];
+        if let Some(list) = processor["outputs"].as_object() {
+            for (path, sha1) in list {
+                outputs.push((
+                    self.resolve_argument(path)?,
+                    self.resolve_argument(sha1.as_str().unwrap_or(""))?,
+                ));
+            }
+        }
+        Ok(outputs)
+    }
+
+    fn check_outputs(&self, outputs: &[(String, String)]) -> Result<(), String> {
+        for (path, sha1) in outputs {
+            super::downloader::verify_file(path, Some(sha1), None)?;
+        }
+        Ok(())
+    }
+}
+
+// Values are either a library ([group:name:version]), a literal ('value') or a plain string.
+fn resolve_value(value: &str, lib_dir: &str) -> String {
+    if let Some(library) = value.strip_prefix('[').and_then(|v| v.strip_suffix(']')) {
+        format!("{}/{}", lib_dir, get_library_path(library))
+    } else if let Some(literal) = value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')) {
+        literal.to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+fn read_entry(archive: &mut ZipArchive<BufReader<File>>, name: &str) -> Result<String, String> {
+    let mut file = match archive.by_name(name.trim_start_matches('/')) {
+        Ok(ok) => ok,
+        Err(e) => return Err(format!("{} was not found in the installer: {}", name, e)),
+    };
+    let mut content = String::new();
+    match file.read_to_string(&mut content) {
+        Ok(_) => Ok(content),
+        Err(e) => Err(format!("failed to read {}: {}", name, e)),
+    }
+}
+
+fn extract_entry(
+    archive: &mut ZipArchive<BufReader<File>>,
+    name: &str,
+    path: &str,
+) -> Result<(), String> {
+    let mut file = match archive.by_name(name.trim_start_matches('/')) {
+        Ok(ok) => ok,
+        Err(e) => return Err(format!("{} was not found in the installer: {}", name, e)),
+    };
+    write_entry(&mut file, path)
+}
+
+fn write_entry(file: &mut impl Read, path: &str) -> Result<(), String> {
+    if let Some(parent) = Path::new(path).parent() {
+        if let Err(e) = fs::create_dir_all(parent) {
+            return Err(e.to_string());
+        }
+    }
+    let mut content = vec![];
+    if let Err(e) = file.read_to_end(&mut content) {
+        return Err(format!("failed to extract {}: {}", path, e));
+    }
+    match write_file_atomically(path, &content) {
+        Ok(ok) => Ok(ok),
+        Err(e) => Err(format!("failed to extract {}: {}", path, e)),
+    }
+}
+
+fn get_main_class(jar: &str) -> Result<String, String> {
+    let file = match File::open(jar) {
+        Ok(ok) => ok,
+        Err(e) => return Err(format!("{}: {}", jar, e)),
+    };
+    let mut archive = match ZipArchive::new(BufReader::new(file)) {
+        Ok(ok) => ok,
+        Err(e) => return Err(format!("{}: {}", jar, e)),
+    };
+    let manifest = read_entry(&mut archive, "META-INF/MANIFEST.MF")?;
+    match manifest
+        .lines()
+        .find_map(|line| line.strip_prefix("Main-Class:"))
+    {
+        Some(main_class) => Ok(main_class.trim().to_string()),
+        None => Err(format!("{} has no main class", jar)),
+    }
+}
+
+// Forge versions grouped by game version, newest first.
+pub async fn get_forge_versions(
+    endpoints: Endpoints,
+) -> Result<Vec<(String, Vec<LoaderVersion>)>, String> {
+    let client = super::network::client();
+    let p = match get_cached_manifest(
+        &client,
+        &endpoints.resolve(
+            "https://files.minecraftforge.net/net/minecraftforge/forge/maven-metadata.json",
+        ),
+        "forge_versions",
+    )
+    .await
+    {
+        Ok(ok) => ok,
+        Err(e) => return Err(format!("failed to get forge download list: {}", e)),
+    };
+
+    let mut versions = vec![];
+    if let Some(game_versions) = p.as_object() {
+        for (game_version, loaders) in game_versions {
+            let mut loaderversionlist = vec![];
+            // forge lists its versions oldest first.
+            for i in loaders.as_array().into_iter().flatten().rev() {
+                loaderversionlist.push(LoaderVersion {
+                    version: i.as_str().unwrap().to_owned(),
+                    stable: true,
+                })
+            }
+            versions.push((game_version.clone(), loaderversionlist));
+        }
+    }
+
+    sort_game_versions(versions, &client, &endpoints).await
+}
+
+// NeoForge versions grouped by game version, newest first.
+pub async fn get_neoforge_versions(
+    showallversions: bool,
+    endpoints: Endpoints,
+) -> Result<Vec<(String, Vec<LoaderVersion>)>, String> {
+    let client = super::network::client();
+    let p = match get_cached_manifest(
+        &client,
+        &endpoints.resolve(
+            "https://maven.neoforged.net/api/maven/versions/releases/net/neoforged/neoforge",
+        ),
+        "neoforge_versions",
+    )
+    .await
+    {
+        Ok(ok) => ok,
+        Err(e) => return Err(format!("failed to get neoforge download list: {}", e)),
+    };
+
+    let mut versions: Vec<(String, Vec<LoaderVersion>)> = vec![];
+    for i in p["versions"].as_array().into_iter().flatten().rev() {
+        let version = i.as_str().unwrap().to_owned();
+        let loader = LoaderVersion {
+            stable: !version.contains("beta") && !version.contains("alpha"),
+            version,
+        };
+        if !showallversions && !loader.stable {
+            continue;
+        }
+
+        let game_version = get_neoforge_game_version(&loader.version);
+        match versions.iter_mut().find(|(game, _)| *game == game_version) {
+            Some((_, loaders)) => loaders.push(loader),
+            None => versions.push((game_version, vec![loader])),
+        }
+    }
+
+    sort_game_versions(versions, &client, &endpoints).await
+}
+
+// NeoForge versions start with the game version without its leading "1.",
+// so 20.4.80 is for 1.20.4 and 21.0.1 is for 1.21.
+fn get_neoforge_game_version(version: &str) -> String {
+    let mut pieces = version.split(['.', '-']);
+    let major = pieces.next().unwrap_or("");
+    match pieces.next() {
+        Some("0") | None => format!("1.{}", major),
+        Some(minor) => format!("1.{}.{}", major, minor),
+    }
+}
+
+// Orders game versions like mojang's manifest does, leaving out the ones it doesn't know about
+// and the ones without any loader.
+async fn sort_game_versions(
+    mut versions: Vec<(String, Vec<LoaderVersion>)>,
+    client: &Client,
+    endpoints: &Endpoints,
+) -> Result<Vec<(String, Vec<LoaderVersion>)>, String> {
+    let manifest =
+        match get_cached_manifest(client, &endpoints.version_manifest(), "version_manifest_v2")
+            .await
+        {
+            Ok(ok) => ok,
+            Err(e) => return Err(format!("failed to get download list: {}", e)),
+        };
+
+    let mut sorted = vec![];
+    for i in manifest["versions"].as_array().into_iter().flatten() {
+        if let Some(index) = versions.iter().position(|(game, _)| i["id"] == *game) {
+            let version = versions.swap_remove(index);
+            if !version.1.is_empty() {
+                sorted.push(version)
+            }
+        }
+    }
+    Ok(sorted)
+}
@@ -0,0 +1,211 @@
+use super::downloader::{get_cached_manifest, Download, Endpoints};
+use reqwest::Client;
+use serde_json::Value;
+use std::{fs, path::Path};
+
+#[cfg(target_os = "linux")]
+use std::os::unix::prelude::PermissionsExt;
+
+// Mojang publishes the java runtimes used by the official launcher. Each version json names the
+// runtime it needs in javaVersion.component, and every runtime is a list of files with hashes.
+const RUNTIMES_MANIFEST: &str = "https://piston-meta.mojang.com/v1/products/java-runtime/2ec0cc96c44e5a76b9c8b7c39df7210883d12871/all.json";
+
+// Versions older than the javaVersion field ran on java 8.
+const DEFAULT_COMPONENT: &str = "jre-legacy";
+
+// A runtime whose files are being downloaded. Executable bits and links can only be set up once
+// the files are there.
+pub struct JavaRuntime {
+    folder: String,
+    version: String,
+    executables: Vec<String>,
+    links: Vec<(String, String)>,
+}
+
+// Runtime component and java major version required by a vanilla version json.
+pub fn get_required_java(version_json: &Value) -> (String, i64) {
+    match version_json["javaVersion"]["component"].as_str() {
+        Some(component) => (
+            component.to_string(),
+            version_json["javaVersion"]["majorVersion"]
+                .as_i64()
+                .unwrap_or(8),
+        ),
+        None => (DEFAULT_COMPONENT.to_string(), 8),
+    }
+}
+
+pub fn get_runtime_folder(component: &str) -> String {
+    format!(
+        "{}/siglauncher_java/{}",
+        super::launcher::get_minecraft_dir(),
+        component
+    )
+}
+
+pub fn get_java_path(component: &str) -> String {
+    match std::env::consts::OS {
+        "windows" => format!("{}/bin/javaw.exe", get_runtime_folder(component)),
+        _ => format!("{}/bin/java", get_runtime_folder(component)),
+    }
+}
+
+// A runtime only counts as installed once all of its files were downloaded.
+pub fn is_installed(component: &str) -> bool {
+    Path::new(&format!("{}/.version", get_runtime_folder(component))).exists()
+        && Path::new(&get_java_path(component)).exists()
+}
+
+// Name mojang uses for this system in the runtimes manifest.
+fn get_platform() -> Option<&'static str> {
+    match (std::env::consts::OS, std::env::consts::ARCH) {
+        ("linux", "x86_64") => Some("linux"),
+        ("linux", "x86") => Some("linux-i386"),
+        ("windows", "x86_64") => Some("windows-x64"),
+        ("windows", "x86") => Some("windows-x86"),
+        ("windows", "aarch64") => Some("windows-arm64"),
+        _ => None,
+    }
+}
+
+// Lists the files of a runtime that still have to be downloaded, creating its folders.
+pub async fn get_runtime_files(
+    client: &Client,
+    component: &str,
+    endpoints: &Endpoints,
+) -> Result<(Vec<Download>, JavaRuntime), String> {
+    let platform = match get_platform() {
+        Some(platform) => platform,
+        None => {
+            return Err(format!(
+                "mojang doesn't provide java for {} {}, add a JVM manually",
+                std::env::consts::OS,
+                std::env::consts::ARCH
+            ))
+        }
+    };
+
+    let runtimes = match get_cached_manifest(
+        client,
+        &endpoints.resolve(RUNTIMES_MANIFEST),
+        "java_runtimes",
+    )
+    .await
+    {
+        Ok(ok) => ok,
+        Err(e) => return Err(format!("failed to get java runtimes: {}", e)),
+    };
+
+    let runtime = match runtimes[platform][component]
+        .as_array()
+        .and_then(|r| r.first())
+    {
+        Some(runtime) => runtime,
+        None => return Err(format!("{} is not available for {}", component, platform)),
+    };
+
+    let manifest: Value = match client
+        .get(endpoints.resolve(runtime["manifest"]["url"].as_str().unwrap_or("")))
+        .timeout(super::network::read_timeout())
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+    {
+        Ok(ok) => match ok.text().await.map(|text| serde_json::from_str(&text)) {
+            Ok(Ok(ok)) => ok,
+            Ok(Err(e)) => return Err(format!("failed to read {} manifest: {}", component, e)),
+            Err(e) => return Err(format!("failed to download {} manifest: {}", component, e)),
+        },
+        Err(e) => return Err(format!("failed to download {} manifest: {}", component, e)),
+    };
+
+    let folder = get_runtime_folder(component);
+    let mut download_list = vec![];
+    let mut executables = vec![];
+    let mut links = vec![];
+
+    // without this check a runtime with no files would be marked as installed.
+    let files = match manifest["files"]
+        .as_object()
+        .filter(|files| !files.is_empty())
+    {
+        Some(files) => files,
+        None => return Err(format!("{} manifest doesn't list any files", component)),
+    };
+    for (name, file) in files {
+        let path = format!("{}/{}", folder, name);
+        match file["type"].as_str() {
+            Some("directory") => match fs::create_dir_all(&path) {
+                Ok(ok) => ok,
+                Err(e) => return Err(e.to_string()),
+            },
+            Some("file") => {
+                if let Some(parent) = Path::new(&path).parent() {
+                    match fs::create_dir_all(parent) {
+                        Ok(ok) => ok,
+                        Err(e) => return Err(e.to_string()),
+                    }
+                }
+                if file["executable"] == true {
+                    executables.push(path.clone())
+                }
+                if !Path::new(&path).exists() {
+                    let raw = &file["downloads"]["raw"];
+                    download_list.push(Download {
+                        path,
+                        url: endpoints.resolve(raw["url"].as_str().unwrap_or("")),
+                        sha1: raw["sha1"].as_str().map(|sha1| sha1.to_string()),
+                        size: raw["size"].as_u64(),
+                        extract: None,
+                    })
+                }
+            }
+            Some("link") => links.push((path, file["target"].as_str().unwrap_or("").to_string())),
+            _ => {}
+        }
+    }
+
+    Ok((
+        download_list,
+        JavaRuntime {
+            folder,
+            version: runtime["version"]["name"]
+                .as_str()
+                .unwrap_or(component)
+                .to_string(),
+            executables,
+            links,
+        },
+    ))
+}
+
+impl JavaRuntime {
+    // Marks the executables and creates the links once every file was downloaded.
+    pub fn finish(&self) -> Result<(), String> {
+        #[cfg(target_os = "linux")]
+        for path in &self.executables {
+            let mut permission = match fs::metadata(path) {
+                Ok(ok) => ok.permissions(),
+                Err(e) => return Err(format!("{}: {}", path, e)),
+            };
+            permission.set_mode(0o755);
+            if let Err(e) = fs::set_permissions(path, permission) {
+                return Err(format!("{}: {}", path, e));
+            }
+        }
+
+        #[cfg(target_os = "linux")]
+        for (path, target) in &self.links {
+            if fs::symlink_metadata(path).is_err() {
+                if let Err(e) = std::os::unix::fs::symlink(target, path) {
+                    return Err(format!("{}: {}", path, e));
+                }
+            }
+        }
+
+        match fs::write(format!("{}/.version", self.folder), &self.version) {
+            Ok(ok) => Ok(ok),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+}
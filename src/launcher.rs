@@ -1,11 +1,12 @@
 use iced::subscription;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::{
     collections::HashMap,
     env,
     fs::{self, File},
     hash::Hash,
-    io::{BufRead, BufReader, Read, Write},
+    io::{BufRead, BufReader, Read},
     path::Path,
     process::{Command, Stdio},
     sync::mpsc::{self, Receiver},
@@ -30,8 +31,8 @@ pub enum Progress {
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum Missing {
-    Java8,
-    Java17,
+    // java runtime component, like java-runtime-gamma.
+    Java(String),
     VersionFiles(Vec<super::downloader::Download>),
     VanillaJson(String, String),
 }
@@ -57,6 +58,21 @@ pub struct GameSettings {
     pub autojava: bool,
     pub game_wrapper_commands: Vec<String>,
     pub enviroment_variables: HashMap<String, String>,
+    pub endpoints: super::downloader::Endpoints,
+    // width and height of the game window, the game picks them when it's None.
+    pub resolution: Option<(u32, u32)>,
+    pub fullscreen: bool,
+    pub quick_play: Option<QuickPlay>,
+}
+
+// Where the game goes right after starting, instead of the title screen.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum QuickPlay {
+    // host:port, the port can be left out.
+    Multiplayer(String),
+    // name of the world folder in the saves folder.
+    Singleplayer(String),
+    Realms(String),
 }
 async fn launcher<I: Copy>(id: I, state: State) -> ((I, Progress), State) {
     match state {
@@ -146,13 +162,36 @@ async fn launcher<I: Copy>(id: I, state: State) -> ((I, Progress), State) {
             if !Path::new(&version_jar_path).exists() {
                 missing_files_list.push(super::downloader::Download {
                     path: version_jar_path,
-                    url: p["downloads"]["client"]["url"]
+                    url: game_settings
+                        .endpoints
+                        .resolve(p["downloads"]["client"]["url"].as_str().unwrap()),
+                    sha1: p["downloads"]["client"]["sha1"]
                         .as_str()
-                        .unwrap()
-                        .to_string(),
+                        .map(|sha1| sha1.to_string()),
+                    size: p["downloads"]["client"]["size"].as_u64(),
+                    extract: None,
                 })
             }
 
+            match super::downloader::get_log_config(&minecraft_dir, &p, &game_settings.endpoints) {
+                // mojang replaced the configurations of old versions with patched ones after
+                // CVE-2021-44228, so an outdated one is downloaded again.
+                Ok(Some(log_config)) => {
+                    if !Path::new(&log_config.path).exists()
+                        || super::downloader::verify_file(
+                            &log_config.path,
+                            log_config.sha1.as_deref(),
+                            log_config.size,
+                        )
+                        .is_err()
+                    {
+                        missing_files_list.push(log_config)
+                    }
+                }
+                Ok(None) => {}
+                Err(e) => println!("Failed to get the log configuration, ignoring. -> {e}"),
+            }
+
             let is_natives_folder_empty = match fs::read_dir(format!("{}/natives", version_dir)) {
                 Ok(ok) => ok.count() == 0,
                 Err(_) => {
@@ -166,11 +205,12 @@ async fn launcher<I: Copy>(id: I, state: State) -> ((I, Progress), State) {
                 &minecraft_dir,
                 p["libraries"].as_array().unwrap(),
                 &version_dir,
+                &game_settings.endpoints,
             ) {
                 Ok(ok) => {
                     for i in ok {
                         if !Path::new(&i.path).exists() {
-                            if i.path.contains("natives.jar") {
+                            if i.extract.is_some() {
                                 if is_natives_folder_empty {
                                     missing_files_list.push(i);
                                     continue;
@@ -182,59 +222,52 @@ async fn launcher<I: Copy>(id: I, state: State) -> ((I, Progress), State) {
                         }
                     }
                 }
-                Err(e) => println!("Failed to get libraries, ignoring. -> {e}"),
-            }
-
-            //check for asset index and downloads it if doesn't exist
-            let asset_index_path = format!(
-                "{}/assets/indexes/{}.json",
-                minecraft_dir,
-                p["assets"].as_str().unwrap()
-            );
-            if !Path::new(&asset_index_path).exists() {
-                match reqwest::get(p["assetIndex"]["url"].as_str().unwrap()).await {
-                    Ok(ok) => {
-                        let bytes = ok.bytes().await.unwrap();
-
-                        match fs::create_dir_all(format!("{}/assets/indexes", minecraft_dir)) {
-                            Ok(ok) => ok,
-                            Err(e) => {
-                                println!("Failed to create asset index directory, ignoring. -> {e}")
-                            }
-                        }
-                        match File::create(&asset_index_path) {
-                            Ok(mut ok) => match ok.write_all(&bytes) {
-                                Ok(ok) => ok,
-                                Err(e) => {
-                                    println!("Failed to write to asset index, ignoring. -> {e}")
-                                }
-                            },
-                            Err(e) => println!("Failed to create asset index, ignoring. -> {e}"),
-                        }
-                    }
-                    Err(e) => println!("Failed to download asset index, ignoring. -> {e}"),
-                };
+                Err(e) => return ((id, Progress::Errored(e)), State::Idle),
             }
 
-            if Path::new(&format!(
-                "{}/assets/indexes/{}.json",
-                minecraft_dir,
-                p["assets"].as_str().unwrap()
-            ))
-            .exists()
+            //check for asset index and downloads it if doesn't exist or is corrupted
+            let asset_index = match super::downloader::get_asset_index(
+                &minecraft_dir,
+                &p,
+                &game_settings.endpoints,
+            ) {
+                Ok(ok) => ok,
+                Err(e) => return ((id, Progress::Errored(e)), State::Idle),
+            };
+            if !Path::new(&asset_index.path).exists()
+                || super::downloader::verify_file(
+                    &asset_index.path,
+                    asset_index.sha1.as_deref(),
+                    asset_index.size,
+                )
+                .is_err()
             {
-                let asset_p = super::getjson(asset_index_path);
+                if let Err(e) = super::downloader::download_single_file(
+                    super::network::client(),
+                    asset_index.clone(),
+                )
+                .await
+                {
+                    return (
+                        (
+                            id,
+                            Progress::Errored(format!("Failed to download the asset index: {e}")),
+                        ),
+                        State::Idle,
+                    );
+                }
+            }
 
-                match super::downloader::get_assets(&minecraft_dir, asset_p) {
-                    Ok(ok) => {
-                        for i in ok {
-                            if !Path::new(&i.path).exists() {
-                                missing_files_list.push(i)
-                            }
+            let asset_p = super::getjson(asset_index.path);
+            match super::downloader::get_assets(&minecraft_dir, asset_p, &game_settings.endpoints) {
+                Ok(ok) => {
+                    for i in ok {
+                        if !Path::new(&i.path).exists() {
+                            missing_files_list.push(i)
                         }
                     }
-                    Err(e) => println!("Failed to get assets, ignoring. -> {e}"),
                 }
+                Err(e) => println!("Failed to get assets, ignoring. -> {e}"),
             }
 
             if !missing_files_list.is_empty() {
@@ -248,22 +281,12 @@ async fn launcher<I: Copy>(id: I, state: State) -> ((I, Progress), State) {
             }
             // check for java
             if game_settings.autojava {
-                let java_version = if let Some(java) = p["javaVersion"]["majorVersion"].as_i64() {
-                    java
-                } else if let Some(java) = p["javaVersion"]["Version"].as_i64() {
-                    java
-                } else {
-                    17
-                };
-
-                if java_version > 8
-                    && !Path::new(&format!("{}/siglauncher_java/java17", minecraft_dir)).exists()
-                {
-                    return ((id, Progress::Checked(Some(Missing::Java17))), State::Idle);
-                } else if java_version == 8
-                    && !Path::new(&format!("{}/siglauncher_java/java8", minecraft_dir)).exists()
-                {
-                    return ((id, Progress::Checked(Some(Missing::Java8))), State::Idle);
+                let (component, _) = super::java_runtime::get_required_java(&p);
+                if !super::java_runtime::is_installed(&component) {
+                    return (
+                        (id, Progress::Checked(Some(Missing::Java(component)))),
+                        State::Idle,
+                    );
                 }
             }
 
@@ -307,53 +330,72 @@ async fn launcher<I: Copy>(id: I, state: State) -> ((I, Progress), State) {
             // } json file
 
             let main_class = &p["mainClass"].as_str().unwrap();
-            let asset_index = p["assets"].as_str().unwrap_or("").to_string();
+            // loaders only list what they add to the vanilla json.
+            let vanilla_json = p["inheritsFrom"].as_str().map(|vanilla| {
+                super::getjson(format!(
+                    "{}/versions/{}/{}.json",
+                    &minecraft_directory, game_settings.game_version, vanilla
+                ))
+            });
+            let base_json = vanilla_json.as_ref().unwrap_or(&p);
+
+            // loader jsons usually leave the asset index to the vanilla json.
+            let asset_index = p["assets"]
+                .as_str()
+                .or(base_json["assets"].as_str())
+                .unwrap_or("")
+                .to_string();
+            let game_assets = match get_game_assets(&minecraft_directory, &asset_index, &game_dir) {
+                Ok(ok) => ok,
+                Err(e) => return ((id, Progress::Errored(e)), State::Idle),
+            };
             let native_directory = format!(
                 "{}/versions/{}/natives",
                 &minecraft_directory, game_settings.game_version
             );
 
             let mut library_list = libmanager(&p);
+            if let Some(vanilla_json) = &vanilla_json {
+                library_list.push_str(&libmanager(vanilla_json));
+            }
+            library_list.push_str(&format!(
+                "{}/versions/{}/{}.jar",
+                &minecraft_directory, game_settings.game_version, game_settings.game_version
+            ));
 
-            let mut version_jvm_args = get_game_jvm_args(&p, &native_directory);
-
-            //
-            let mut version_game_args = vec![];
-
-            // uuid
-
-            let uuid = get_uuid_from_api(&game_settings.username).await;
+            let uuid = get_uuid_from_api(&game_settings.username, &game_settings.endpoints).await;
+
+            let mut arguments = ArgumentBuilder {
+                auth_player_name: game_settings.username,
+                auth_uuid: uuid,
+                auth_access_token: String::from("[pro]"),
+                version_name: game_settings.game_version.clone(),
+                version_type: base_json["type"].as_str().unwrap_or("release").to_string(),
+                game_directory: game_dir.to_string(),
+                assets_root: assets_dir,
+                assets_index_name: asset_index,
+                game_assets,
+                natives_directory: native_directory,
+                library_directory: format!("{}/libraries", &minecraft_directory),
+                classpath: library_list,
+                resolution: game_settings.resolution,
+                fullscreen: game_settings.fullscreen,
+                ..Default::default()
+            };
+            if let Some(quick_play) = &game_settings.quick_play {
+                if let Err(e) = arguments.set_quick_play(quick_play, base_json) {
+                    return ((id, Progress::Errored(e)), State::Idle);
+                }
+            }
 
-            // this is used to get game args.
-            let gamedata = vec![
-                game_settings.username,
-                game_settings.game_version.clone(),
-                game_dir.to_string(),
-                assets_dir,
-                asset_index,
-                uuid,
-                String::from("[pro]"),
-                String::from("{}"),
-                String::from("legacy"),
-                String::from("Release"),
-                String::from("Modified"),
-                library_list.clone(),
-            ];
+            let mut version_jvm_args = arguments.jvm_arguments(&p, vanilla_json.as_ref());
+            version_jvm_args.extend(get_logging_args(base_json));
+            let version_game_args = arguments.game_arguments(&p, vanilla_json.as_ref());
 
-            let is_modded = if game_settings.game_version.to_lowercase().contains("fabric")
+            let is_modded = game_settings.game_version.to_lowercase().contains("fabric")
                 || game_settings.game_version.to_lowercase().contains("forge")
-                || !p["inheritsFrom"].is_null()
-            {
-                let (modded_jvm_args, modded_game_args, vanilla_version_library_list) =
-                    modded(&p, &game_settings.game_version, gamedata.clone());
-                version_jvm_args.extend(modded_jvm_args);
-                library_list.push_str(&vanilla_version_library_list);
-
-                version_game_args = modded_game_args;
-                true
-            } else {
-                false
-            };
+                || game_settings.game_version.to_lowercase().contains("quilt")
+                || vanilla_json.is_some();
 
             let (java_path, java_args) = match game_settings.autojava {
                 true => automatic_java(p.clone(), &game_settings.game_version, is_modded),
@@ -363,32 +405,6 @@ async fn launcher<I: Copy>(id: I, state: State) -> ((I, Progress), State) {
                 ),
             };
 
-            library_list.push_str(&format!(
-                "{}/versions/{}/{}.jar",
-                &minecraft_directory, game_settings.game_version, game_settings.game_version
-            ));
-
-            if let Some(arguments) = p["arguments"]["game"].as_array() {
-                let mut str_arguments = vec![];
-                for i in arguments {
-                    if i.is_string() {
-                        str_arguments.push(i.as_str().unwrap_or("").to_owned())
-                    } else if i["value"].is_string() {
-                        str_arguments.push(i["value"].as_str().unwrap().to_owned())
-                    }
-                }
-
-                version_game_args.extend_from_slice(&get_game_args(str_arguments, &gamedata));
-            } else if let Some(arguments) = p["minecraftArguments"].as_str() {
-                let oldargs: Vec<String> = arguments
-                    .to_string()
-                    .split_whitespace()
-                    .map(String::from)
-                    .collect();
-
-                version_game_args.extend_from_slice(&get_game_args(oldargs, &gamedata))
-            }
-
             let mut wrapper_commands = game_settings.game_wrapper_commands;
             let has_wrapper_commands;
 
@@ -408,8 +424,6 @@ async fn launcher<I: Copy>(id: I, state: State) -> ((I, Progress), State) {
                 .arg(format!("-Xmx{}M", game_settings.ram * 1024.))
                 .args(java_args.clone())
                 .args(version_jvm_args.clone())
-                .arg("-cp")
-                .arg(library_list.clone())
                 .arg(main_class)
                 .args(version_game_args.clone());
             game_command.envs(game_settings.enviroment_variables);
@@ -509,6 +523,89 @@ pub fn get_minecraft_dir() -> String {
     }
 }
 
+// Space taken by each part of the minecraft folder, in bytes.
+#[derive(Debug, Clone, Default)]
+pub struct DiskUsage {
+    pub total: u64,
+    pub versions: Vec<(String, u64)>,
+    pub libraries: u64,
+    pub assets: u64,
+    pub java_runtimes: Vec<(String, u64)>,
+    // profile sizes include their own logs and crash reports.
+    pub profiles: Vec<(String, u64)>,
+    pub logs: u64,
+    pub crash_reports: u64,
+}
+
+// Walking the whole minecraft folder takes a while, so it runs outside of the async executor.
+pub async fn get_disk_usage() -> Result<DiskUsage, String> {
+    match tokio::task::spawn_blocking(|| {
+        let mc_dir = Path::new(&get_minecraft_dir()).to_path_buf();
+        let profiles_dir = mc_dir.join("siglauncher_profiles");
+
+        // logs and crash reports of the default profile are in the minecraft folder itself.
+        let mut game_dirs = vec![mc_dir.clone()];
+        game_dirs.extend(
+            get_folder_sizes(&profiles_dir)
+                .into_iter()
+                .map(|(name, _)| profiles_dir.join(name)),
+        );
+
+        DiskUsage {
+            total: get_size(&mc_dir),
+            versions: get_folder_sizes(&mc_dir.join("versions")),
+            libraries: get_size(&mc_dir.join("libraries")),
+            assets: get_size(&mc_dir.join("assets")),
+            java_runtimes: get_folder_sizes(&mc_dir.join("siglauncher_java")),
+            profiles: get_folder_sizes(&profiles_dir),
+            logs: game_dirs
+                .iter()
+                .map(|dir| get_size(&dir.join("logs")))
+                .sum(),
+            crash_reports: game_dirs
+                .iter()
+                .map(|dir| get_size(&dir.join("crash-reports")))
+                .sum(),
+        }
+    })
+    .await
+    {
+        Ok(usage) => Ok(usage),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+// Size of every folder inside of a folder, biggest first.
+fn get_folder_sizes(folder: &Path) -> Vec<(String, u64)> {
+    let mut sizes = match fs::read_dir(folder) {
+        Ok(entries) => entries
+            .flatten()
+            .filter(|entry| entry.path().is_dir())
+            .map(|entry| {
+                (
+                    entry.file_name().to_string_lossy().to_string(),
+                    get_size(&entry.path()),
+                )
+            })
+            .collect(),
+        Err(_) => vec![],
+    };
+    sizes.sort_unstable_by(|(_, a): &(String, u64), (_, b)| b.cmp(a));
+    sizes
+}
+
+// Size of a file, or of everything inside of a folder. Links are not followed.
+pub fn get_size(path: &Path) -> u64 {
+    match fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.is_dir() => match fs::read_dir(path) {
+            Ok(entries) => entries.flatten().map(|entry| get_size(&entry.path())).sum(),
+            Err(_) => 0,
+        },
+        Ok(metadata) => metadata.len(),
+        Err(_) => 0,
+    }
+}
+
 pub async fn getinstalledversions() -> Vec<String> {
     let versions_dir = format!("{}/versions", get_minecraft_dir());
 
@@ -527,157 +624,386 @@ pub async fn getinstalledversions() -> Vec<String> {
             }
         })
         .collect::<Vec<_>>();
-    versions.sort_unstable_by(|a, b| get_version_order(b, a));
+    // newest first, versions without a json go last.
+    versions.sort_by_cached_key(|version| std::cmp::Reverse(get_release_time(version)));
     versions
 }
 
-fn get_version_order(a: &str, b: &str) -> std::cmp::Ordering {
-    let split_a: Vec<&str> = a.split(|c| c == '.' || c == '-').collect();
-    let split_b: Vec<&str> = b.split(|c| c == '.' || c == '-').collect();
+// When a version came out, from its json. Loaders copy it from the game version they are for.
+fn get_release_time(version: &str) -> Option<String> {
+    let content = fs::read_to_string(format!(
+        "{}/versions/{}/{}.json",
+        get_minecraft_dir(),
+        version,
+        version
+    ))
+    .ok()?;
+    let version_json: Value = serde_json::from_str(&content).ok()?;
+    version_json["releaseTime"]
+        .as_str()
+        .map(|time| time.to_owned())
+}
+// } Utility functions
+
+// Launch functions {
+// Fills in the arguments of a version json. Placeholders like ${version_name} can be anywhere in
+// an argument, and arguments with rules are only used when they apply to this system and to the
+// features that are turned on.
+#[derive(Debug, Clone, Default)]
+struct ArgumentBuilder {
+    auth_player_name: String,
+    auth_uuid: String,
+    auth_access_token: String,
+    version_name: String,
+    version_type: String,
+    game_directory: String,
+    assets_root: String,
+    assets_index_name: String,
+    game_assets: String,
+    natives_directory: String,
+    library_directory: String,
+    classpath: String,
+    // width and height of the game window.
+    resolution: Option<(u32, u32)>,
+    fullscreen: bool,
+    // where the game logs what it joined through quick play.
+    quick_play_path: Option<String>,
+    quick_play_singleplayer: Option<String>,
+    quick_play_multiplayer: Option<String>,
+    quick_play_realms: Option<String>,
+    // host and port for versions from before quick play.
+    server: Option<(String, String)>,
+}
 
-    if let (Some(major_a), Some(major_b)) = (
-        split_a.first().and_then(|v| v.parse::<i32>().ok()),
-        split_b.first().and_then(|v| v.parse::<i32>().ok()),
-    ) {
-        if major_a != major_b {
-            return major_a.cmp(&major_b);
+impl ArgumentBuilder {
+    fn features(&self) -> Vec<&'static str> {
+        let mut features = vec![];
+        if self.resolution.is_some() {
+            features.push("has_custom_resolution")
         }
-    } else {
-        return match (
-            split_a.first().map(|v| v.parse::<i32>().is_ok()),
-            split_b.first().map(|v| v.parse::<i32>().is_ok()),
-        ) {
-            (Some(true), Some(false)) => std::cmp::Ordering::Greater,
-            (Some(false), Some(true)) => std::cmp::Ordering::Less,
-            _ => std::cmp::Ordering::Equal,
+        if self.quick_play_path.is_some() {
+            features.push("has_quick_plays_support")
+        }
+        if self.quick_play_singleplayer.is_some() {
+            features.push("is_quick_play_singleplayer")
+        }
+        if self.quick_play_multiplayer.is_some() {
+            features.push("is_quick_play_multiplayer")
+        }
+        if self.quick_play_realms.is_some() {
+            features.push("is_quick_play_realms")
+        }
+        features
+    }
+
+    fn variable(&self, name: &str) -> Option<String> {
+        let value = match name {
+            "auth_player_name" => self.auth_player_name.clone(),
+            "auth_uuid" => self.auth_uuid.clone(),
+            // old versions call the access token a session.
+            "auth_access_token" | "auth_session" => self.auth_access_token.clone(),
+            // only microsoft accounts have these.
+            "auth_xuid" | "clientid" => String::from("0"),
+            "user_type" => String::from("legacy"),
+            "user_properties" => String::from("{}"),
+            "version_name" => self.version_name.clone(),
+            "version_type" => self.version_type.clone(),
+            "game_directory" => self.game_directory.clone(),
+            "assets_root" => self.assets_root.clone(),
+            "assets_index_name" => self.assets_index_name.clone(),
+            "game_assets" => self.game_assets.clone(),
+            "natives_directory" => self.natives_directory.clone(),
+            "library_directory" => self.library_directory.clone(),
+            "classpath" => self.classpath.clone(),
+            "classpath_separator" => String::from(get_classpath_separator()),
+            "launcher_name" => String::from("siglauncher"),
+            "launcher_version" => String::from(env!("CARGO_PKG_VERSION")),
+            "resolution_width" => self.resolution?.0.to_string(),
+            "resolution_height" => self.resolution?.1.to_string(),
+            "quick_play_path" => self.quick_play_path.clone()?,
+            "quick_play_singleplayer" => self.quick_play_singleplayer.clone()?,
+            "quick_play_multiplayer" => self.quick_play_multiplayer.clone()?,
+            "quick_play_realms" => self.quick_play_realms.clone()?,
+            _ => return None,
         };
+        Some(value)
     }
 
-    if let (Some(minor_a), Some(minor_b)) = (
-        split_a.get(1).and_then(|v| v.parse::<i32>().ok()),
-        split_b.get(1).and_then(|v| v.parse::<i32>().ok()),
-    ) {
-        if minor_a != minor_b {
-            return minor_a.cmp(&minor_b);
+    // Replaces every ${placeholder} in an argument, unknown ones are left as they are.
+    fn substitute(&self, argument: &str) -> String {
+        let mut result = String::new();
+        let mut rest = argument;
+        while let Some(start) = rest.find("${") {
+            let Some(length) = rest[start..].find('}') else {
+                break;
+            };
+            let name = &rest[start + 2..start + length];
+            result.push_str(&rest[..start]);
+            match self.variable(name) {
+                Some(value) => result.push_str(&value),
+                None => {
+                    println!("Unknown argument placeholder {}", name);
+                    result.push_str(&rest[start..=start + length])
+                }
+            }
+            rest = &rest[start + length + 1..];
         }
-    } else {
-        return match (
-            split_a.get(1).map(|v| v.parse::<i32>().is_ok()),
-            split_b.get(1).map(|v| v.parse::<i32>().is_ok()),
-        ) {
-            (Some(true), Some(false)) => std::cmp::Ordering::Greater,
-            (Some(false), Some(true)) => std::cmp::Ordering::Less,
-            _ => std::cmp::Ordering::Equal,
+        result.push_str(rest);
+        result
+    }
+
+    // Arguments from the "arguments" section of version jsons since 1.13. Each one is a string,
+    // or rules with a value that can be a string or a list of them.
+    fn arguments(&self, arguments: &Value) -> Vec<String> {
+        let features = self.features();
+        let mut result = vec![];
+        for argument in arguments.as_array().into_iter().flatten() {
+            if let Some(argument) = argument.as_str() {
+                result.push(self.substitute(argument));
+                continue;
+            }
+            if !super::rules::is_allowed(&argument["rules"], &features) {
+                continue;
+            }
+            match &argument["value"] {
+                Value::String(value) => result.push(self.substitute(value)),
+                Value::Array(values) => result.extend(
+                    values
+                        .iter()
+                        .filter_map(|value| value.as_str())
+                        .map(|value| self.substitute(value)),
+                ),
+                _ => {}
+            }
+        }
+        result
+    }
+
+    // Game arguments of the vanilla json followed by the ones of the loader. Older versions have
+    // them in a single string, and loaders that have one replace the vanilla one.
+    fn game_arguments(&self, version_json: &Value, vanilla_json: Option<&Value>) -> Vec<String> {
+        let base_json = vanilla_json.unwrap_or(version_json);
+        let mut arguments = match version_json["minecraftArguments"]
+            .as_str()
+            .or(base_json["minecraftArguments"].as_str())
+        {
+            Some(arguments) => self.legacy_arguments(arguments),
+            None => self.arguments(&base_json["arguments"]["game"]),
         };
+        if vanilla_json.is_some() && version_json["minecraftArguments"].is_null() {
+            arguments.extend(self.arguments(&version_json["arguments"]["game"]));
+        }
+
+        // versions without the has_custom_resolution feature still take these, and no version json
+        // has a fullscreen argument.
+        if let Some((width, height)) = self.resolution {
+            if !arguments.iter().any(|argument| argument == "--width") {
+                arguments.extend([
+                    String::from("--width"),
+                    width.to_string(),
+                    String::from("--height"),
+                    height.to_string(),
+                ]);
+            }
+        }
+        if self.fullscreen && !arguments.iter().any(|argument| argument == "--fullscreen") {
+            arguments.push(String::from("--fullscreen"));
+        }
+        if let Some((host, port)) = &self.server {
+            arguments.extend([
+                String::from("--server"),
+                host.clone(),
+                String::from("--port"),
+                port.clone(),
+            ]);
+        }
+        arguments
     }
 
-    if let (Some(release_a), Some(release_b)) = (
-        split_a.get(2).and_then(|v| v.parse::<i32>().ok()),
-        split_b.get(2).and_then(|v| v.parse::<i32>().ok()),
-    ) {
-        if release_a != release_b {
-            return release_a.cmp(&release_b);
+    // Versions since 1.20 declare quick play features in the rules of their game arguments. Older
+    // ones can only join a server, with --server and --port.
+    fn set_quick_play(
+        &mut self,
+        quick_play: &QuickPlay,
+        vanilla_json: &Value,
+    ) -> Result<(), String> {
+        let supports = |feature: &str| {
+            vanilla_json["arguments"]["game"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .any(|argument| super::rules::uses_feature(&argument["rules"], feature))
+        };
+        match quick_play {
+            QuickPlay::Multiplayer(address) if supports("is_quick_play_multiplayer") => {
+                self.quick_play_multiplayer = Some(address.clone())
+            }
+            QuickPlay::Multiplayer(address) => self.server = Some(split_address(address)),
+            QuickPlay::Singleplayer(world) if supports("is_quick_play_singleplayer") => {
+                self.quick_play_singleplayer = Some(world.clone())
+            }
+            QuickPlay::Realms(realm) if supports("is_quick_play_realms") => {
+                self.quick_play_realms = Some(realm.clone())
+            }
+            _ => {
+                return Err(format!(
+                    "{} can't open worlds or realms on start, that needs 1.20 or newer.",
+                    self.version_name
+                ))
+            }
         }
-    } else {
-        return match (
-            split_a.get(2).map(|v| v.parse::<i32>().is_ok()),
-            split_b.get(2).map(|v| v.parse::<i32>().is_ok()),
-        ) {
-            (Some(true), Some(false)) => std::cmp::Ordering::Greater,
-            (Some(false), Some(true)) => std::cmp::Ordering::Less,
-            _ => std::cmp::Ordering::Equal,
+
+        if supports("has_quick_plays_support") {
+            let log_folder = format!("{}/quickPlay/java", self.game_directory);
+            if let Err(e) = fs::create_dir_all(&log_folder) {
+                return Err(format!("{}: {}", log_folder, e));
+            }
+            self.quick_play_path = Some(format!("{}/{}.json", log_folder, self.version_name));
+        }
+        Ok(())
+    }
+
+    // Versions from before 1.13 don't list any jvm arguments, so they get the natives and the
+    // classpath here.
+    fn jvm_arguments(&self, version_json: &Value, vanilla_json: Option<&Value>) -> Vec<String> {
+        let base_json = vanilla_json.unwrap_or(version_json);
+        let jvm_arguments = &base_json["arguments"]["jvm"];
+        let mut arguments = match jvm_arguments.is_array() {
+            true => self.arguments(jvm_arguments),
+            false => vec![format!("-Djava.library.path={}", self.natives_directory)],
         };
+        if !jvm_arguments.to_string().contains("${classpath}") {
+            arguments.extend([String::from("-cp"), self.classpath.clone()]);
+        }
+        if vanilla_json.is_some() {
+            arguments.extend(self.arguments(&version_json["arguments"]["jvm"]));
+        }
+        arguments
     }
 
-    split_a
-        .get(3)
-        .unwrap_or(&"")
-        .cmp(split_b.get(3).unwrap_or(&""))
+    fn legacy_arguments(&self, arguments: &str) -> Vec<String> {
+        arguments
+            .split_whitespace()
+            .map(|argument| self.substitute(argument))
+            .collect()
+    }
 }
-// } Utility functions
 
-// Launch functions {
-fn get_game_args(arguments: Vec<String>, gamedata: &[String]) -> Vec<String> {
-    let mut version_game_args = vec![];
-    for i in arguments {
-        match i.as_str() {
-            "${auth_player_name}" => version_game_args.push(gamedata[0].clone()),
-            "${version_name}" => version_game_args.push(gamedata[1].clone()),
-            "${game_directory}" => version_game_args.push(gamedata[2].clone()),
-            "${assets_root}" => version_game_args.push(gamedata[3].clone()),
-            "${assets_index_name}" => version_game_args.push(gamedata[4].clone()),
-            "${auth_uuid}" => version_game_args.push(gamedata[5].clone()),
-            "${auth_session}" => version_game_args.push(gamedata[5].clone()),
-            "${clientid}" => version_game_args.push(gamedata[5].clone()),
-            "${auth_xuid}" => version_game_args.push(gamedata[5].clone()),
-            "${auth_access_token}" => version_game_args.push(gamedata[6].clone()),
-            "${user_properties}" => version_game_args.push(gamedata[7].clone()),
-            "${user_type}" => version_game_args.push(gamedata[8].clone()),
-            "${version_type}" => version_game_args.push(gamedata[9].clone()),
-            "${classpath}" => version_game_args.push(gamedata[10].clone()),
-            "${game_assets}" => {
-                version_game_args.push(format!("{}/resources", get_minecraft_dir()))
-            }
-
-            "--demo" => {}
-            _ => version_game_args.push(i.to_owned()),
+// Splits host:port, with 25565 as the default port. IPv6 hosts need brackets around them.
+fn split_address(address: &str) -> (String, String) {
+    match address.rsplit_once(':') {
+        Some((host, port))
+            if port.parse::<u16>().is_ok() && (!host.contains(':') || host.starts_with('[')) =>
+        {
+            (
+                host.trim_start_matches('[')
+                    .trim_end_matches(']')
+                    .to_string(),
+                port.to_string(),
+            )
         }
+        _ => (address.to_string(), String::from("25565")),
     }
-    version_game_args
 }
 
-fn get_game_jvm_args(p: &Value, nativedir: &str) -> Vec<String> {
-    let lib_dir = format!("{}/libraries", get_minecraft_dir());
-    let separator = match std::env::consts::OS {
-        "linux" => ":",
+pub(super) fn get_classpath_separator() -> &'static str {
+    match std::env::consts::OS {
         "windows" => ";",
-        _ => panic!(),
+        _ => ":",
+    }
+}
+
+// Versions before 1.7.3 look for assets by their name instead of their hash. Virtual asset indexes
+// want them in assets/virtual/<index>, and the ones mapped to resources (before 1.6) in the
+// resources folder of the game directory, so the objects are copied there.
+// Returns the folder the game gets as ${game_assets}.
+fn get_game_assets(mc_dir: &str, asset_index: &str, game_dir: &str) -> Result<String, String> {
+    let assets_dir = format!("{}/assets", mc_dir);
+    let index_path = format!("{}/indexes/{}.json", assets_dir, asset_index);
+    if !Path::new(&index_path).exists() {
+        return Ok(assets_dir);
+    }
+    let index = super::getjson(index_path);
+
+    let target = if index["map_to_resources"] == true {
+        format!("{}/resources", game_dir)
+    } else if index["virtual"] == true {
+        format!("{}/virtual/{}", assets_dir, asset_index)
+    } else {
+        return Ok(assets_dir);
     };
 
-    let mut version_jvm_args = vec![];
-    if let Some(arguments) = p["arguments"]["jvm"].as_array() {
-        for i in arguments {
-            if i.is_string() {
-                let mut value = i.as_str().unwrap().to_string();
+    if let Some(objects) = index["objects"].as_object() {
+        for (name, object) in objects {
+            let hash = object["hash"].as_str().unwrap_or("");
+            // names come from the index, they shouldn't leave the target folder.
+            if hash.len() < 2 || name.split('/').any(|part| part == "..") {
+                continue;
+            }
+            let path = format!("{}/{}", target, name);
+            if Path::new(&path).exists() {
+                continue;
+            }
 
-                if value.contains("${natives_directory}") {
-                    value = value.replace("${natives_directory}", nativedir);
+            if let Some(parent) = Path::new(&path).parent() {
+                if let Err(e) = fs::create_dir_all(parent) {
+                    return Err(format!("{}: {}", parent.display(), e));
                 }
+            }
+            let object_path = format!("{}/objects/{}/{}", assets_dir, &hash[0..2], hash);
+            if let Err(e) = fs::copy(&object_path, &path) {
+                return Err(format!("failed to copy {} to {}: {}", object_path, path, e));
+            }
+        }
+    }
 
-                if value.contains("${library_directory}") {
-                    value = value.replace("${library_directory}", &lib_dir);
-                }
+    Ok(target)
+}
 
-                if value.contains("${classpath_separator}") {
-                    value = value.replace("${classpath_separator}", separator);
-                }
+// Points log4j to the configuration from the version json. Versions shipping a log4j affected by
+// CVE-2021-44228 (1.7 to 1.18.0) also get lookups turned off, in case their configuration is an
+// old unpatched one or couldn't be downloaded.
+fn get_logging_args(p: &Value) -> Vec<String> {
+    let mut logging_args = vec![];
 
-                if value != "${classpath}" || value != "-cp" {
-                    version_jvm_args.push(value.to_string())
-                }
+    if let Some(path) = super::downloader::get_log_config_path(&get_minecraft_dir(), p) {
+        if let Some(argument) = p["logging"]["client"]["argument"].as_str() {
+            if Path::new(&path).exists() {
+                logging_args.push(argument.replace("${path}", &path))
             }
         }
-    } else {
-        version_jvm_args.push(format!("-Djava.library.path={}", &nativedir))
     }
-    version_jvm_args
+
+    let vulnerable = p["libraries"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|library| {
+            library["name"]
+                .as_str()?
+                .strip_prefix("org.apache.logging.log4j:log4j-core:")
+        })
+        .any(is_vulnerable_log4j);
+    if vulnerable {
+        logging_args.push(String::from("-Dlog4j2.formatMsgNoLookups=true"))
+    }
+
+    logging_args
+}
+
+// Lookups were fixed in log4j 2.15.
+fn is_vulnerable_log4j(version: &str) -> bool {
+    let mut numbers = version
+        .split(['.', '-'])
+        .map(|n| n.parse::<u32>().unwrap_or(0));
+    let major = numbers.next().unwrap_or(0);
+    let minor = numbers.next().unwrap_or(0);
+    major == 2 && minor < 15
 }
 
 fn automatic_java(mut p: Value, game_version: &String, ismodded: bool) -> (String, Vec<String>) {
     let mc_dir = get_minecraft_dir();
 
-    let (autojava17path, autojava8path) = if std::env::consts::OS == "windows" {
-        (
-            format!("{}/siglauncher_java/java17/bin/javaw.exe", mc_dir),
-            format!("{}/siglauncher_java/java8/bin/javaw.exe", mc_dir),
-        )
-    } else {
-        (
-            format!("{}/siglauncher_java/java17/bin/java", mc_dir),
-            format!("{}/siglauncher_java/java8/bin/java", mc_dir),
-        )
-    };
-
     if ismodded {
         let vanillaversion = p["inheritsFrom"].as_str().unwrap_or(game_version.as_str());
         let vanillajsonpathstring = format!(
@@ -691,22 +1017,23 @@ fn automatic_java(mut p: Value, game_version: &String, ismodded: bool) -> (Strin
         vanillajson.read_to_string(&mut vjsoncontent).unwrap();
         p = serde_json::from_str(&vjsoncontent).unwrap();
     }
-    let requiredjavaversion = p["javaVersion"]["majorVersion"].as_i64().unwrap_or(0);
+    let (component, requiredjavaversion) = super::java_runtime::get_required_java(&p);
+    let autojavapath = super::java_runtime::get_java_path(&component);
 
     let java17args = "-XX:+UnlockExperimentalVMOptions -XX:+UnlockDiagnosticVMOptions -XX:+AlwaysActAsServerClassMachine -XX:+AlwaysPreTouch -XX:+DisableExplicitGC -XX:+UseNUMA -XX:NmethodSweepActivity=1 -XX:ReservedCodeCacheSize=400M -XX:NonNMethodCodeHeapSize=12M -XX:ProfiledCodeHeapSize=194M -XX:NonProfiledCodeHeapSize=194M -XX:-DontCompileHugeMethods -XX:MaxNodeLimit=240000 -XX:NodeLimitFudgeFactor=8000 -XX:+UseVectorCmov -XX:+PerfDisableSharedMem -XX:+UseFastUnorderedTimeStamps -XX:+UseCriticalJavaThreadPriority -XX:ThreadPriorityPolicy=1 -XX:AllocatePrefetchStyle=3";
+    // the method sweeper and its flag were removed in java 20.
+    let java21args = "-XX:+UnlockExperimentalVMOptions -XX:+UnlockDiagnosticVMOptions -XX:+AlwaysActAsServerClassMachine -XX:+AlwaysPreTouch -XX:+DisableExplicitGC -XX:+UseNUMA -XX:ReservedCodeCacheSize=400M -XX:NonNMethodCodeHeapSize=12M -XX:ProfiledCodeHeapSize=194M -XX:NonProfiledCodeHeapSize=194M -XX:-DontCompileHugeMethods -XX:MaxNodeLimit=240000 -XX:NodeLimitFudgeFactor=8000 -XX:+UseVectorCmov -XX:+PerfDisableSharedMem -XX:+UseFastUnorderedTimeStamps -XX:+UseCriticalJavaThreadPriority -XX:ThreadPriorityPolicy=1 -XX:AllocatePrefetchStyle=3";
     let java8args = "-XX:+UnlockExperimentalVMOptions -XX:+UnlockDiagnosticVMOptions -XX:+AlwaysActAsServerClassMachine -XX:+ParallelRefProcEnabled -XX:+DisableExplicitGC -XX:+AlwaysPreTouch -XX:+AggressiveOpts -XX:MaxInlineLevel=15 -XX:MaxVectorSize=32 -XX:ThreadPriorityPolicy=1 -XX:+UseNUMA -XX:+UseDynamicNumberOfGCThreads -XX:NmethodSweepActivity=1 -XX:ReservedCodeCacheSize=350M -XX:-DontCompileHugeMethods -XX:MaxNodeLimit=240000 -XX:NodeLimitFudgeFactor=8000 -Dgraal.CompilerConfiguration=community";
 
-    if requiredjavaversion > 8 || requiredjavaversion == 0 {
-        (
-            autojava17path,
-            java17args.split(' ').map(|s| s.to_owned()).collect(),
-        )
-    } else {
-        (
-            autojava8path,
-            java8args.split(' ').map(|s| s.to_owned()).collect(),
-        )
-    }
+    let args = match requiredjavaversion {
+        ..=8 => java8args,
+        9..=19 => java17args,
+        _ => java21args,
+    };
+    (
+        autojavapath,
+        args.split(' ').map(|s| s.to_owned()).collect(),
+    )
 }
 
 fn libmanager(p: &Value) -> String {
@@ -729,127 +1056,71 @@ fn libmanager(p: &Value) -> String {
             Old,
         }
 
-        for library in libraries {
-            if library["rules"][0]["os"]["name"] == os
-                || library["rules"][0]["os"]["name"].is_null()
-            {
-                let libraryname = library["name"].as_str().unwrap();
-                let mut lpieces: Vec<&str> = libraryname.split(':').collect();
-                let firstpiece = lpieces[0].replace('.', "/");
-                lpieces.remove(0);
-
-                let lib_type = if library["name"]
-                    .as_str()
-                    .unwrap()
-                    .contains(&format!("natives-{}", os))
-                {
-                    LibraryType::Natives
-                } else if library["natives"][os].is_null() {
-                    LibraryType::Normal
-                } else {
-                    LibraryType::Old
-                };
+        for library in &super::rules::get_system_libraries(libraries) {
+            let libraryname = library["name"].as_str().unwrap();
+            let mut lpieces: Vec<&str> = libraryname.split(':').collect();
+            let firstpiece = lpieces[0].replace('.', "/");
+            lpieces.remove(0);
 
-                match lib_type {
-                    LibraryType::Natives => {
-                        let last_piece = lpieces.pop().unwrap();
-
-                        let libpath = format!(
-                            "{}{}/{}/{}-{}-{}.jar",
-                            lib_dir,
-                            &firstpiece,
-                            &lpieces.join("/"),
-                            &lpieces[&lpieces.len() - 2],
-                            &lpieces[&lpieces.len() - 1],
-                            last_piece
-                        );
-
-                        library_list.push_str(&libpath);
-                        library_list.push(separator);
-                    }
-                    LibraryType::Normal => {
-                        let libpath = format!(
-                            "{}{}/{}/{}-{}.jar",
-                            lib_dir,
-                            &firstpiece,
-                            &lpieces.join("/"),
-                            &lpieces[&lpieces.len() - 2],
-                            &lpieces[&lpieces.len() - 1]
-                        );
-
-                        library_list.push_str(&libpath);
-                        library_list.push(separator);
-                    }
-                    LibraryType::Old => {
-                        let libpath = format!(
-                            "{}{}/{}/{}-{}-natives-{}.jar",
-                            lib_dir,
-                            &firstpiece,
-                            &lpieces.join("/"),
-                            &lpieces[&lpieces.len() - 2],
-                            &lpieces[&lpieces.len() - 1],
-                            os
-                        );
-
-                        library_list.push_str(&libpath);
-                        library_list.push(separator);
-                    }
-                }
-            }
-        }
-    }
-    library_list
-}
-
-fn modded(
-    p: &Value,
-    game_version: &String,
-    mut gamedata: Vec<String>,
-) -> (Vec<String>, Vec<String>, String) {
-    let mc_dir = get_minecraft_dir();
-
-    let mut modded_game_args = vec![];
+            let lib_type = if library["name"]
+                .as_str()
+                .unwrap()
+                .contains(&format!("natives-{}", os))
+            {
+                LibraryType::Natives
+            } else if library["natives"][os].is_null() {
+                LibraryType::Normal
+            } else {
+                LibraryType::Old
+            };
 
-    let vanillaversion = p["inheritsFrom"].as_str().unwrap();
-    let vanillajsonpathstring = format!(
-        "{}/versions/{}/{}.json",
-        &mc_dir, game_version, vanillaversion
-    );
+            match lib_type {
+                LibraryType::Natives => {
+                    let last_piece = lpieces.pop().unwrap();
+
+                    let libpath = format!(
+                        "{}{}/{}/{}-{}-{}.jar",
+                        lib_dir,
+                        &firstpiece,
+                        &lpieces.join("/"),
+                        &lpieces[&lpieces.len() - 2],
+                        &lpieces[&lpieces.len() - 1],
+                        last_piece
+                    );
 
-    let mut vanillajson = File::open(&vanillajsonpathstring).unwrap();
+                    library_list.push_str(&libpath);
+                    library_list.push(separator);
+                }
+                LibraryType::Normal => {
+                    let libpath = format!(
+                        "{}{}",
+                        lib_dir,
+                        super::downloader::get_library_path(libraryname)
+                    );
 
-    let mut vjsoncontent = String::new();
-    vanillajson.read_to_string(&mut vjsoncontent).unwrap();
-    let vjson: Value = serde_json::from_str(&vjsoncontent).unwrap();
-    let new_asset_index = vjson["assets"].as_str().unwrap().to_string();
-    gamedata[4] = new_asset_index;
+                    library_list.push_str(&libpath);
+                    library_list.push(separator);
+                }
+                LibraryType::Old => {
+                    let libpath = format!(
+                        "{}{}/{}/{}-{}-natives-{}.jar",
+                        lib_dir,
+                        &firstpiece,
+                        &lpieces.join("/"),
+                        &lpieces[&lpieces.len() - 2],
+                        &lpieces[&lpieces.len() - 1],
+                        os
+                    );
 
-    if let Some(arguments) = vjson["arguments"]["game"].as_array() {
-        let mut base_arguments = Vec::new();
-        for i in arguments {
-            if i.is_string() {
-                base_arguments.push(i.as_str().unwrap().to_string())
-            } else if i["value"].is_string() {
-                base_arguments.push(i["value"].as_str().unwrap().to_string())
+                    library_list.push_str(&libpath);
+                    library_list.push(separator);
+                }
             }
         }
-
-        modded_game_args = get_game_args(base_arguments, &gamedata)
     }
-
-    let vanilla_version_jvm_args = get_game_jvm_args(
-        &vjson,
-        &format!("{}/versions/{}/natives", &mc_dir, game_version),
-    );
-
-    let vanilla_library_list = &libmanager(&vjson);
-
-    (
-        vanilla_version_jvm_args,
-        modded_game_args,
-        vanilla_library_list.to_string(),
-    )
+    library_list
 }
+
 // } Launch functions
 
 fn command_exists(command_name: &str) -> bool {
@@ -870,12 +1141,15 @@ fn command_exists(command_name: &str) -> bool {
     false
 }
 
-async fn get_uuid_from_api(username: &str) -> String {
-    match reqwest::get(format!(
-        "https://api.mojang.com/users/profiles/minecraft/{}",
-        username
-    ))
-    .await
+async fn get_uuid_from_api(username: &str, endpoints: &super::downloader::Endpoints) -> String {
+    match super::network::client()
+        .get(endpoints.resolve(&format!(
+            "https://api.mojang.com/users/profiles/minecraft/{}",
+            username
+        )))
+        .timeout(super::network::read_timeout())
+        .send()
+        .await
     {
         Ok(response) => match response.error_for_status() {
             Ok(uuid) => match uuid.text().await {
@@ -905,3 +1179,121 @@ fn generate_uuid(username: &str) -> String {
     let uuid = Uuid::from_slice(hash.as_slice()).unwrap();
     uuid.to_string()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn log4j_before_2_15_is_vulnerable() {
+        assert!(is_vulnerable_log4j("2.0-beta9"));
+        assert!(is_vulnerable_log4j("2.8.1"));
+        assert!(is_vulnerable_log4j("2.14.1"));
+    }
+
+    #[test]
+    fn fixed_log4j_is_not_vulnerable() {
+        assert!(!is_vulnerable_log4j("2.15.0"));
+        assert!(!is_vulnerable_log4j("2.17.1"));
+        assert!(!is_vulnerable_log4j("2.19.0"));
+        // log4j 1 has no lookups.
+        assert!(!is_vulnerable_log4j("1.2.17"));
+    }
+
+    fn test_arguments() -> ArgumentBuilder {
+        ArgumentBuilder {
+            auth_player_name: String::from("Steve"),
+            version_name: String::from("1.20.1"),
+            game_directory: String::from("/games/minecraft"),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn substitute_fills_in_placeholders() {
+        let arguments = test_arguments();
+        assert_eq!(arguments.substitute("${auth_player_name}"), "Steve");
+        assert_eq!(
+            arguments.substitute("-Dminecraft.launcher.brand=${launcher_name}"),
+            "-Dminecraft.launcher.brand=siglauncher"
+        );
+        assert_eq!(
+            arguments.substitute("${game_directory}/versions/${version_name}"),
+            "/games/minecraft/versions/1.20.1"
+        );
+        // old versions call the access token a session.
+        assert_eq!(
+            arguments.substitute("${auth_session}"),
+            arguments.substitute("${auth_access_token}")
+        );
+    }
+
+    #[test]
+    fn substitute_keeps_unknown_placeholders() {
+        let arguments = test_arguments();
+        assert_eq!(arguments.substitute("${unknown}"), "${unknown}");
+        // unset optional values are unknown too.
+        assert_eq!(
+            arguments.substitute("${resolution_width}"),
+            "${resolution_width}"
+        );
+        assert_eq!(arguments.substitute("${version_name"), "${version_name");
+        assert_eq!(arguments.substitute("--demo"), "--demo");
+    }
+
+    #[test]
+    fn arguments_follow_rules_and_features() {
+        let game_arguments = json!([
+            "--username",
+            "${auth_player_name}",
+            {
+                "rules": [{ "action": "allow", "features": { "has_custom_resolution": true } }],
+                "value": ["--width", "${resolution_width}", "--height", "${resolution_height}"]
+            },
+            {
+                "rules": [{ "action": "allow", "features": { "is_demo_user": true } }],
+                "value": "--demo"
+            }
+        ]);
+
+        let arguments = test_arguments();
+        assert_eq!(
+            arguments.arguments(&game_arguments),
+            vec!["--username", "Steve"]
+        );
+
+        let arguments = ArgumentBuilder {
+            resolution: Some((1280, 720)),
+            ..test_arguments()
+        };
+        assert_eq!(
+            arguments.arguments(&game_arguments),
+            vec!["--username", "Steve", "--width", "1280", "--height", "720"]
+        );
+    }
+
+    #[test]
+    fn arguments_for_other_systems_are_skipped() {
+        let other_os = match std::env::consts::OS {
+            "windows" => "linux",
+            _ => "windows",
+        };
+        let jvm_arguments = json!([
+            {
+                "rules": [{ "action": "allow", "os": { "name": other_os } }],
+                "value": "-XstartOnFirstThread"
+            },
+            "-Djava.library.path=${natives_directory}"
+        ]);
+        let arguments = ArgumentBuilder {
+            natives_directory: String::from("/natives"),
+            ..test_arguments()
+        };
+
+        assert_eq!(
+            arguments.arguments(&jvm_arguments),
+            vec!["-Djava.library.path=/natives"]
+        );
+    }
+}
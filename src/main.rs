@@ -22,8 +22,14 @@ use std::{
 use widget::Renderer;
 
 mod downloader;
+mod forge;
+mod java_runtime;
 mod launcher;
+mod network;
+mod rules;
 mod theme;
+mod uninstall;
+mod verify;
 
 fn main() -> iced::Result {
     let icon = include_bytes!("icons/siglauncher.png");
@@ -58,19 +64,31 @@ struct Siglauncher {
     current_game_profile: String,
     game_wrapper_commands: String,
     game_enviroment_variables: String,
+    game_width: String,
+    game_height: String,
+    game_fullscreen: bool,
+    quick_play_type: QuickPlayType,
+    quick_play_target: String,
     show_all_versions_in_download_list: bool,
 
     all_versions: Vec<String>,
     java_name_list: Vec<String>,
     game_profile_list: Vec<String>,
-    vanilla_versions_download_list: Vec<String>,
-    fabric_versions_download_list: Vec<String>,
-    vanilla_version_to_download: String,
-    fabric_version_to_download: String,
+    version_browser: Vec<downloader::GameVersion>,
+    version_search: String,
+    version_types_shown: Vec<&'static str>,
+    loader_to_install: Loader,
+    version_to_download: String,
+    required_java_versions: HashMap<String, i64>,
+    fabric_loader_download_list: Vec<downloader::LoaderVersion>,
+    fabric_loader_to_download: Option<downloader::LoaderVersion>,
+    quilt_loader_download_list: Vec<downloader::LoaderVersion>,
+    quilt_loader_to_download: Option<downloader::LoaderVersion>,
+    forge_versions_download_list: Vec<(String, Vec<downloader::LoaderVersion>)>,
+    forge_loader_to_download: Option<downloader::LoaderVersion>,
+    neoforge_versions_download_list: Vec<(String, Vec<downloader::LoaderVersion>)>,
+    neoforge_loader_to_download: Option<downloader::LoaderVersion>,
     download_text: String,
-    files_download_number: i32,
-
-    needs_to_update_download_list: bool,
 
     jvm_to_add_name: String,
     jvm_to_add_path: String,
@@ -78,8 +96,16 @@ struct Siglauncher {
 
     game_profile_to_add: String,
 
+    version_to_uninstall: String,
+    uninstall_plan: Option<uninstall::Cleanup>,
+    uninstall_text: String,
+
+    disk_usage: Option<launcher::DiskUsage>,
+    disk_usage_text: String,
+
     restrict_launch: bool,
-    java_download_size: u8,
+    concurrent_downloads: usize,
+    endpoints: downloader::Endpoints,
 }
 
 #[derive(PartialEq, Debug, Clone, Default)]
@@ -92,7 +118,81 @@ enum Screen {
     GameProfile,
     Logs,
     ModifyCommand,
+    Downloads,
+    Uninstall,
+    DiskUsage,
+}
+// What gets installed on top of a game version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum Loader {
+    #[default]
+    Vanilla,
+    Fabric,
+    Quilt,
+    Forge,
+    NeoForge,
+}
+
+impl Loader {
+    const ALL: [Loader; 5] = [
+        Loader::Vanilla,
+        Loader::Fabric,
+        Loader::Quilt,
+        Loader::Forge,
+        Loader::NeoForge,
+    ];
+}
+
+impl std::fmt::Display for Loader {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Loader::Vanilla => write!(f, "Vanilla"),
+            Loader::Fabric => write!(f, "Fabric"),
+            Loader::Quilt => write!(f, "Quilt"),
+            Loader::Forge => write!(f, "Forge"),
+            Loader::NeoForge => write!(f, "NeoForge"),
+        }
+    }
+}
+
+// What the game joins on start, picked on the main screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum QuickPlayType {
+    #[default]
+    TitleScreen,
+    Server,
+    World,
+    Realm,
+}
+
+impl QuickPlayType {
+    const ALL: [QuickPlayType; 4] = [
+        QuickPlayType::TitleScreen,
+        QuickPlayType::Server,
+        QuickPlayType::World,
+        QuickPlayType::Realm,
+    ];
+}
+
+impl std::fmt::Display for QuickPlayType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QuickPlayType::TitleScreen => write!(f, "Title screen"),
+            QuickPlayType::Server => write!(f, "Server"),
+            QuickPlayType::World => write!(f, "World"),
+            QuickPlayType::Realm => write!(f, "Realm"),
+        }
+    }
 }
+
+// Version types of mojang's manifest, with how the installer shows them.
+const VERSION_TYPES: [(&str, &str); 4] = [
+    ("release", "Release"),
+    ("snapshot", "Snapshot"),
+    ("old_beta", "Beta"),
+    ("old_alpha", "Alpha"),
+];
+
 #[derive(Debug, Clone)]
 enum Message {
     LoadVersionList(Vec<String>),
@@ -100,6 +200,11 @@ enum Message {
     Launch,
     ManageGameInfo((usize, launcher::Progress)),
 
+    PauseDownload(usize),
+    ResumeDownload(usize),
+    CancelDownload(usize),
+    ClearFinishedDownloads,
+
     UsernameChanged(String),
     VersionChanged(String),
 
@@ -108,17 +213,35 @@ enum Message {
     GameRamChanged(f64),
     GameWrapperCommandsChanged(String),
     GameEnviromentVariablesChanged(String),
+    GameWidthChanged(String),
+    GameHeightChanged(String),
+    GameFullscreenChanged(bool),
+    QuickPlayTypeChanged(QuickPlayType),
+    QuickPlayTargetChanged(String),
     ShowAllVersionsInDownloadListChanged(bool),
 
-    GotDownloadList(Result<Vec<Vec<String>>, String>),
-    VanillaVersionToDownloadChanged(String),
-    FabricVersionToDownloadChanged(String),
+    GotDownloadList(Result<Vec<downloader::GameVersion>, String>),
+    VersionSearchChanged(String),
+    VersionTypeToggled(&'static str),
+    LoaderToInstallChanged(Loader),
+    VersionToDownloadChanged(String),
+    GotRequiredJavaVersion(String, Result<i64, String>),
+    GotFabricLoaderList(Result<Vec<downloader::LoaderVersion>, String>),
+    FabricLoaderToDownloadChanged(downloader::LoaderVersion),
+    GotQuiltLoaderList(Result<Vec<downloader::LoaderVersion>, String>),
+    QuiltLoaderToDownloadChanged(downloader::LoaderVersion),
+    GotForgeVersionList(Result<Vec<(String, Vec<downloader::LoaderVersion>)>, String>),
+    ForgeLoaderToDownloadChanged(downloader::LoaderVersion),
+    GotNeoForgeVersionList(Result<Vec<(String, Vec<downloader::LoaderVersion>)>, String>),
+    NeoForgeLoaderToDownloadChanged(downloader::LoaderVersion),
     InstallVersion(downloader::VersionType),
     ManageDownload((usize, downloader::Progress)),
     VanillaJson(Value),
 
     OpenGameFolder,
     OpenGameProfileFolder,
+    VerifyInstallation,
+    VerifiedInstallation(String, Result<Vec<(downloader::Download, String)>, String>),
 
     ChangeScreen(Screen),
 
@@ -130,14 +253,174 @@ enum Message {
     GameProfileToAddChanged(String),
     GameProfileAdded,
 
+    VersionToUninstallChanged(String),
+    GotUninstallPlan(String, Result<uninstall::Cleanup, String>),
+    UninstallVersion,
+    Uninstalled(String, Result<u64, String>),
+    GotDiskUsage(Result<launcher::DiskUsage, String>),
+
     GithubButtonPressed,
 }
 
 impl Siglauncher {
+    // Whether a downloader is getting files the game needs before it can be launched.
+    fn launching_downloader(&self, id: usize) -> bool {
+        self.downloaders
+            .iter()
+            .any(|downloader| downloader.id == id && downloader.blocks_launch())
+    }
+
+    // Downloaders can finish or be cancelled in any order, so the length of the list could be
+    // the id of one that is still running.
+    fn next_downloader_id(&self) -> usize {
+        self.downloaders
+            .iter()
+            .map(|downloader| downloader.id + 1)
+            .max()
+            .unwrap_or(0)
+    }
+
+    fn add_job(&mut self, downloader: Downloader) {
+        self.downloaders.push(downloader);
+        self.start_queued_jobs();
+    }
+
+    // Installs run one at a time, in the order they were queued. Java and missing files are
+    // started right away, since the game is waiting for them.
+    fn start_queued_jobs(&mut self) {
+        let mut installing = self.downloaders.iter().any(|downloader| {
+            !downloader.blocks_launch() && matches!(downloader.status, JobStatus::Running(_))
+        });
+        for downloader in &mut self.downloaders {
+            if downloader.status != JobStatus::Queued {
+                continue;
+            }
+            if downloader.blocks_launch() {
+                downloader.status = JobStatus::Running(String::from("Starting."));
+            } else if !installing {
+                downloader.status = JobStatus::Running(String::from("Getting the file list."));
+                installing = true;
+            }
+        }
+
+        // launching stays restricted while java or missing files are still being downloaded.
+        self.restrict_launch = self.downloaders.iter().any(|downloader| {
+            downloader.blocks_launch() && matches!(downloader.status, JobStatus::Running(_))
+        });
+    }
+
+    fn set_job_status(&mut self, id: usize, status: JobStatus) {
+        if let Some(downloader) = self.downloaders.iter_mut().find(|d| d.id == id) {
+            downloader.status = status
+        }
+    }
+
+    // Stops the job's subscription and lets the next queued one start.
+    fn finish_job(&mut self, id: usize, status: JobStatus) {
+        self.set_job_status(id, status);
+        self.start_queued_jobs();
+    }
+
+    // Picks the newest loaders for the version and gets the java version it needs, once.
+    fn select_version_to_download(&mut self, version: String) -> Command<Message> {
+        self.forge_loader_to_download = get_loaders(&self.forge_versions_download_list, &version)
+            .first()
+            .cloned();
+        self.neoforge_loader_to_download =
+            get_loaders(&self.neoforge_versions_download_list, &version)
+                .first()
+                .cloned();
+        self.version_to_download = version.clone();
+
+        if self.required_java_versions.contains_key(&version) {
+            return Command::none();
+        }
+        match self.version_browser.iter().find(|v| v.id == version) {
+            Some(game_version) => Command::perform(
+                downloader::get_required_java_version(
+                    game_version.url.clone(),
+                    self.endpoints.clone(),
+                ),
+                move |result| Message::GotRequiredJavaVersion(version.clone(), result),
+            ),
+            None => Command::none(),
+        }
+    }
+
+    // Versions in the installer that match the search, the version types and the loader.
+    fn shown_versions(&self) -> Vec<&downloader::GameVersion> {
+        let search = self.version_search.to_lowercase();
+        self.version_browser
+            .iter()
+            .filter(|version| {
+                version.id.to_lowercase().contains(&search)
+                    && self
+                        .version_types_shown
+                        .contains(&version.version_type.as_str())
+                    && self.has_loader(version, self.loader_to_install)
+            })
+            .collect()
+    }
+
+    fn has_loader(&self, version: &downloader::GameVersion, loader: Loader) -> bool {
+        match loader {
+            Loader::Vanilla => true,
+            Loader::Fabric => version.fabric,
+            Loader::Quilt => version.quilt,
+            Loader::Forge => {
+                !get_loaders(&self.forge_versions_download_list, &version.id).is_empty()
+            }
+            Loader::NeoForge => {
+                !get_loaders(&self.neoforge_versions_download_list, &version.id).is_empty()
+            }
+        }
+    }
+
+    fn profile_settings(&self) -> ProfileSettings {
+        ProfileSettings {
+            width: self.game_width.parse().ok().filter(|width| *width > 0),
+            height: self.game_height.parse().ok().filter(|height| *height > 0),
+            fullscreen: self.game_fullscreen,
+            quick_play: match (self.quick_play_type, self.quick_play_target.trim()) {
+                (_, "") | (QuickPlayType::TitleScreen, _) => None,
+                (QuickPlayType::Server, target) => {
+                    Some(launcher::QuickPlay::Multiplayer(target.to_string()))
+                }
+                (QuickPlayType::World, target) => {
+                    Some(launcher::QuickPlay::Singleplayer(target.to_string()))
+                }
+                (QuickPlayType::Realm, target) => {
+                    Some(launcher::QuickPlay::Realms(target.to_string()))
+                }
+            },
+        }
+    }
+
+    fn load_profile_settings(&mut self) {
+        let settings = get_profile_settings(&self.current_game_profile);
+        self.game_width = settings.width.map(|w| w.to_string()).unwrap_or_default();
+        self.game_height = settings.height.map(|h| h.to_string()).unwrap_or_default();
+        self.game_fullscreen = settings.fullscreen;
+        (self.quick_play_type, self.quick_play_target) = get_quick_play_fields(&settings);
+    }
+
+    // The game or a download could be using the files of a version.
+    fn is_busy(&self) -> bool {
+        self.launcher.state != LauncherState::Idle
+            || self.downloaders.iter().any(|downloader| {
+                matches!(downloader.status, JobStatus::Queued | JobStatus::Running(_))
+            })
+    }
+
     pub fn launch(&mut self) {
         if updateusersettingsfile(self.username.clone(), self.current_version.clone()).is_err() {
             println!("Failed to save user settings!")
         };
+        // the quick play target is picked on the main screen.
+        let profile_settings = self.profile_settings();
+        if let Err(e) = updateprofilesettingsfile(&self.current_game_profile, &profile_settings) {
+            println!("Failed to save profile settings: {}", e)
+        }
 
         let wrapper_commands_vec: Vec<String> = if !self.game_wrapper_commands.is_empty() {
             self.game_wrapper_commands
@@ -179,6 +462,10 @@ impl Siglauncher {
             game_directory: self.current_game_profile.clone(),
             autojava: self.current_java_name == "Automatic",
             enviroment_variables: enviroment_variables_hash_map,
+            endpoints: self.endpoints.clone(),
+            resolution: profile_settings.width.zip(profile_settings.height),
+            fullscreen: profile_settings.fullscreen,
+            quick_play: profile_settings.quick_play,
         };
         self.launcher.start(game_settings);
         self.logs.clear();
@@ -202,6 +489,12 @@ impl Application for Siglauncher {
         let p: Value = content.unwrap();
         // Configuration file
 
+        let network_settings: network::NetworkSettings =
+            serde_json::from_value(p["network"].clone()).unwrap_or_default();
+        if let Err(e) = network::configure(&network_settings) {
+            println!("Invalid network settings, using the defaults: {}", e)
+        }
+
         // Get Java info
         let mut currentjava = Java {
             name: String::new(),
@@ -261,6 +554,10 @@ impl Application for Siglauncher {
             .collect::<Vec<_>>();
         new_game_profile_list.push("Default".to_string());
 
+        let profile_settings =
+            get_profile_settings(p["current_game_profile"].as_str().unwrap_or("Default"));
+        let (quick_play_type, quick_play_target) = get_quick_play_fields(&profile_settings);
+
         (
             Siglauncher {
                 screen: Screen::Main,
@@ -275,10 +572,23 @@ impl Application for Siglauncher {
                     .as_str()
                     .unwrap()
                     .to_owned(),
+                game_width: profile_settings
+                    .width
+                    .map(|w| w.to_string())
+                    .unwrap_or_default(),
+                game_height: profile_settings
+                    .height
+                    .map(|h| h.to_string())
+                    .unwrap_or_default(),
+                game_fullscreen: profile_settings.fullscreen,
+                quick_play_type,
+                quick_play_target,
                 show_all_versions_in_download_list: p["show_all_versions"].as_bool().unwrap(),
+                concurrent_downloads: p["concurrent_downloads"].as_u64().unwrap() as usize,
+                endpoints: serde_json::from_value(p["endpoints"].clone()).unwrap_or_default(),
                 java_name_list: jvmnames,
                 game_profile_list: new_game_profile_list,
-                needs_to_update_download_list: true,
+                version_types_shown: vec!["release"],
                 ..Default::default()
             },
             Command::perform(launcher::getinstalledversions(), Message::LoadVersionList),
@@ -305,46 +615,37 @@ impl Application for Siglauncher {
                     launcher::Progress::Checked(missing) => {
                         if let Some(missing) = missing {
                             match missing {
-                                launcher::Missing::Java8 => {
-                                    self.launcher.state = LauncherState::Waiting;
-                                    self.downloaders.push(Downloader {
-                                        state: DownloaderState::Idle,
-                                        id: self.downloaders.len(),
-                                    });
-                                    let index = self.downloaders.len() - 1;
-                                    self.downloaders[index].start_java(downloader::Java::J8)
-                                }
-                                launcher::Missing::Java17 => {
+                                launcher::Missing::Java(component) => {
                                     self.launcher.state = LauncherState::Waiting;
-                                    self.downloaders.push(Downloader {
-                                        state: DownloaderState::Idle,
-                                        id: self.downloaders.len(),
-                                    });
-                                    let index = self.downloaders.len() - 1;
-                                    self.downloaders[index].start_java(downloader::Java::J17)
+                                    let mut downloader = Downloader::new(self.next_downloader_id());
+                                    downloader.queue_java(
+                                        component,
+                                        self.concurrent_downloads,
+                                        self.endpoints.clone(),
+                                    );
+                                    self.add_job(downloader)
                                 }
                                 launcher::Missing::VersionFiles(vec) => {
                                     self.game_state_text =
                                         String::from("Found missing files. Starting download.");
                                     self.launcher.state = LauncherState::Waiting;
-                                    self.downloaders.push(Downloader {
-                                        state: DownloaderState::Idle,
-                                        id: self.downloaders.len(),
-                                    });
-                                    let index = self.downloaders.len() - 1;
-                                    self.downloaders[index].start_missing_files(vec)
+                                    let mut downloader = Downloader::new(self.next_downloader_id());
+                                    downloader.queue_missing_files(vec, self.concurrent_downloads);
+                                    self.add_job(downloader)
                                 }
                                 launcher::Missing::VanillaJson(ver, folder) => {
                                     self.launcher.state = LauncherState::Waiting;
                                     self.game_state_text =
                                         String::from("Downloading required json");
+                                    let endpoints = self.endpoints.clone();
                                     return Command::perform(
                                         async move {
                                             match downloader::downloadversionjson(
                                                 &downloader::VersionType::Vanilla,
                                                 &ver,
                                                 &folder,
-                                                &reqwest::Client::new(),
+                                                &network::client(),
+                                                &endpoints,
                                             )
                                             .await
                                             {
@@ -377,6 +678,34 @@ impl Application for Siglauncher {
 
                 Command::none()
             }
+            Message::PauseDownload(id) => {
+                if let Some(downloader) = self.downloaders.iter().find(|d| d.id == id) {
+                    downloader.control.pause()
+                }
+                Command::none()
+            }
+            Message::ResumeDownload(id) => {
+                if let Some(downloader) = self.downloaders.iter().find(|d| d.id == id) {
+                    downloader.control.resume()
+                }
+                Command::none()
+            }
+            Message::CancelDownload(id) => {
+                if let Some(downloader) = self.downloaders.iter_mut().find(|d| d.id == id) {
+                    match downloader.status {
+                        JobStatus::Queued => downloader.status = JobStatus::Cancelled,
+                        // the downloader removes its part files and reports back with Progress::Cancelled.
+                        _ => downloader.control.cancel(),
+                    }
+                }
+                Command::none()
+            }
+            Message::ClearFinishedDownloads => {
+                self.downloaders.retain(|downloader| {
+                    matches!(downloader.status, JobStatus::Queued | JobStatus::Running(_))
+                });
+                Command::none()
+            }
             Message::UsernameChanged(new_username) => {
                 if new_username.len() < 16 {
                     self.username = new_username
@@ -398,6 +727,12 @@ impl Application for Siglauncher {
                         self.show_all_versions_in_download_list,
                     )
                     .unwrap();
+                    if let Err(e) = updateprofilesettingsfile(
+                        &self.current_game_profile,
+                        &self.profile_settings(),
+                    ) {
+                        println!("Failed to save profile settings: {}", e)
+                    }
                 }
 
                 self.screen = new_screen.clone();
@@ -407,18 +742,52 @@ impl Application for Siglauncher {
                         launcher::getinstalledversions(),
                         Message::LoadVersionList,
                     );
-                } else if new_screen == Screen::Installation
-                    && (!self.vanilla_versions_download_list.is_empty()
-                        || !self.fabric_versions_download_list.is_empty()
-                        || self.needs_to_update_download_list)
-                {
-                    let show_all_versions = self.show_all_versions_in_download_list;
+                } else if new_screen == Screen::Uninstall {
+                    self.version_to_uninstall = String::new();
+                    self.uninstall_plan = None;
+                    self.uninstall_text = String::new();
                     return Command::perform(
-                        async move {
-                            downloader::get_downloadable_version_list(show_all_versions).await
-                        },
-                        Message::GotDownloadList,
+                        launcher::getinstalledversions(),
+                        Message::LoadVersionList,
                     );
+                } else if new_screen == Screen::DiskUsage {
+                    self.disk_usage = None;
+                    self.disk_usage_text = String::from("Calculating disk usage.");
+                    return Command::perform(launcher::get_disk_usage(), Message::GotDiskUsage);
+                } else if new_screen == Screen::Installation {
+                    let show_all_versions = self.show_all_versions_in_download_list;
+                    return Command::batch([
+                        Command::perform(
+                            launcher::getinstalledversions(),
+                            Message::LoadVersionList,
+                        ),
+                        Command::perform(
+                            downloader::get_downloadable_version_list(self.endpoints.clone()),
+                            Message::GotDownloadList,
+                        ),
+                        Command::perform(
+                            downloader::get_fabric_loader_versions(
+                                show_all_versions,
+                                self.endpoints.clone(),
+                            ),
+                            Message::GotFabricLoaderList,
+                        ),
+                        Command::perform(
+                            downloader::get_quilt_loader_versions(
+                                show_all_versions,
+                                self.endpoints.clone(),
+                            ),
+                            Message::GotQuiltLoaderList,
+                        ),
+                        Command::perform(
+                            forge::get_forge_versions(self.endpoints.clone()),
+                            Message::GotForgeVersionList,
+                        ),
+                        Command::perform(
+                            forge::get_neoforge_versions(show_all_versions, self.endpoints.clone()),
+                            Message::GotNeoForgeVersionList,
+                        ),
+                    ]);
                 }
 
                 Command::none()
@@ -427,6 +796,56 @@ impl Application for Siglauncher {
                 open::that(launcher::get_minecraft_dir()).unwrap();
                 Command::none()
             }
+            Message::VerifyInstallation => {
+                if self.current_version.is_empty() {
+                    return Command::none();
+                }
+                if self.is_busy() {
+                    self.game_state_text = String::from(
+                        "Wait for the game to close and the downloads to finish before verifying.",
+                    );
+                    return Command::none();
+                }
+                self.game_state_text = format!("Verifying {}.", self.current_version);
+                let version = self.current_version.clone();
+                Command::perform(
+                    verify::verify_installation(version.clone(), self.endpoints.clone()),
+                    move |result| Message::VerifiedInstallation(version.clone(), result),
+                )
+            }
+            Message::VerifiedInstallation(version, result) => {
+                match result {
+                    Ok(broken_files) if broken_files.is_empty() => {
+                        self.game_state_text = format!("All files of {} are fine.", version)
+                    }
+                    // the game could have been started while the files were being checked.
+                    Ok(_) if self.is_busy() => {
+                        self.game_state_text = format!(
+                            "Can't repair {} while the game or a download is running.",
+                            version
+                        )
+                    }
+                    Ok(broken_files) => {
+                        for (_, problem) in &broken_files {
+                            println!("{}", problem)
+                        }
+                        self.game_state_text = format!(
+                            "{} files of {} are missing or corrupted, repairing.",
+                            broken_files.len(),
+                            version
+                        );
+                        let mut downloader = Downloader::new(self.next_downloader_id());
+                        downloader.queue_repair(
+                            version,
+                            broken_files.into_iter().map(|(file, _)| file).collect(),
+                            self.concurrent_downloads,
+                        );
+                        self.add_job(downloader)
+                    }
+                    Err(e) => self.game_state_text = format!("Failed to verify {}: {}", version, e),
+                }
+                Command::none()
+            }
             Message::OpenGameProfileFolder => {
                 if self.current_game_profile == "Default" {
                     open::that(launcher::get_minecraft_dir()).unwrap();
@@ -474,7 +893,38 @@ impl Application for Siglauncher {
                 Command::none()
             }
             Message::GameProfileChanged(new_game_profile) => {
+                // each profile has its own window settings.
+                if let Err(e) =
+                    updateprofilesettingsfile(&self.current_game_profile, &self.profile_settings())
+                {
+                    println!("Failed to save profile settings: {}", e)
+                }
                 self.current_game_profile = new_game_profile;
+                self.load_profile_settings();
+                Command::none()
+            }
+            Message::GameWidthChanged(width) => {
+                if width.len() <= 5 && width.chars().all(|c| c.is_ascii_digit()) {
+                    self.game_width = width
+                }
+                Command::none()
+            }
+            Message::GameHeightChanged(height) => {
+                if height.len() <= 5 && height.chars().all(|c| c.is_ascii_digit()) {
+                    self.game_height = height
+                }
+                Command::none()
+            }
+            Message::GameFullscreenChanged(fullscreen) => {
+                self.game_fullscreen = fullscreen;
+                Command::none()
+            }
+            Message::QuickPlayTypeChanged(quick_play_type) => {
+                self.quick_play_type = quick_play_type;
+                Command::none()
+            }
+            Message::QuickPlayTargetChanged(target) => {
+                self.quick_play_target = target;
                 Command::none()
             }
             Message::GameRamChanged(new_ram) => {
@@ -486,50 +936,154 @@ impl Application for Siglauncher {
                 Command::none()
             }
             Message::ShowAllVersionsInDownloadListChanged(bool) => {
-                self.needs_to_update_download_list = true;
                 self.show_all_versions_in_download_list = bool;
                 Command::none()
             }
             Message::GotDownloadList(result) => {
                 match result {
                     Ok(list) => {
-                        self.needs_to_update_download_list = false;
-                        if !list.is_empty() {
-                            self.vanilla_versions_download_list.clear();
-                            self.fabric_versions_download_list.clear();
-                            for i in &list[0] {
-                                let ii = i;
-                                self.vanilla_versions_download_list.push(ii.to_string());
-                            }
-                            for i in &list[1] {
-                                let ii = i;
-                                self.fabric_versions_download_list.push(ii.to_string());
+                        if self.version_to_download.is_empty() {
+                            if let Some(version) = list.first() {
+                                self.version_to_download = version.id.clone()
                             }
                         }
+                        self.version_browser = list;
+                        return self.select_version_to_download(self.version_to_download.clone());
                     }
                     Err(err) => self.download_text = err,
                 }
 
                 Command::none()
             }
-            Message::VanillaVersionToDownloadChanged(new_version) => {
-                self.vanilla_version_to_download = new_version;
+            Message::VersionSearchChanged(search) => {
+                self.version_search = search;
+                Command::none()
+            }
+            Message::VersionTypeToggled(version_type) => {
+                match self
+                    .version_types_shown
+                    .iter()
+                    .position(|t| *t == version_type)
+                {
+                    Some(index) => {
+                        self.version_types_shown.remove(index);
+                    }
+                    None => self.version_types_shown.push(version_type),
+                }
                 Command::none()
             }
-            Message::FabricVersionToDownloadChanged(new_version) => {
-                self.fabric_version_to_download = new_version;
+            Message::LoaderToInstallChanged(loader) => {
+                self.loader_to_install = loader;
                 Command::none()
             }
-            Message::InstallVersion(ver_type) => {
-                let version = match ver_type {
-                    downloader::VersionType::Vanilla => self.vanilla_version_to_download.clone(),
-                    downloader::VersionType::Fabric => self.fabric_version_to_download.clone(),
-                };
-                self.downloaders
-                    .push(Downloader::new(self.downloaders.len()));
+            Message::VersionToDownloadChanged(new_version) => {
+                self.select_version_to_download(new_version)
+            }
+            Message::GotRequiredJavaVersion(version, result) => {
+                match result {
+                    Ok(java) => {
+                        self.required_java_versions.insert(version, java);
+                    }
+                    Err(e) => println!("Failed to get the java version {} needs: {}", version, e),
+                }
+                Command::none()
+            }
+            Message::GotFabricLoaderList(result) => {
+                match result {
+                    Ok(list) => {
+                        // the newest stable loader is picked by default.
+                        if !self
+                            .fabric_loader_to_download
+                            .as_ref()
+                            .is_some_and(|loader| list.contains(loader))
+                        {
+                            self.fabric_loader_to_download =
+                                list.iter().find(|loader| loader.stable).cloned();
+                        }
+                        self.fabric_loader_download_list = list;
+                    }
+                    Err(err) => self.download_text = err,
+                }
+
+                Command::none()
+            }
+            Message::FabricLoaderToDownloadChanged(new_loader) => {
+                self.fabric_loader_to_download = Some(new_loader);
+                Command::none()
+            }
+            Message::GotQuiltLoaderList(result) => {
+                match result {
+                    Ok(list) => {
+                        if !self
+                            .quilt_loader_to_download
+                            .as_ref()
+                            .is_some_and(|loader| list.contains(loader))
+                        {
+                            self.quilt_loader_to_download =
+                                list.iter().find(|loader| loader.stable).cloned();
+                        }
+                        self.quilt_loader_download_list = list;
+                    }
+                    Err(err) => self.download_text = err,
+                }
+
+                Command::none()
+            }
+            Message::QuiltLoaderToDownloadChanged(new_loader) => {
+                self.quilt_loader_to_download = Some(new_loader);
+                Command::none()
+            }
+            Message::GotForgeVersionList(result) => {
+                match result {
+                    Ok(list) => {
+                        self.forge_versions_download_list = list;
+                        self.forge_loader_to_download = get_loaders(
+                            &self.forge_versions_download_list,
+                            &self.version_to_download,
+                        )
+                        .first()
+                        .cloned();
+                    }
+                    Err(err) => self.download_text = err,
+                }
+
+                Command::none()
+            }
+            Message::ForgeLoaderToDownloadChanged(new_loader) => {
+                self.forge_loader_to_download = Some(new_loader);
+                Command::none()
+            }
+            Message::GotNeoForgeVersionList(result) => {
+                match result {
+                    Ok(list) => {
+                        self.neoforge_versions_download_list = list;
+                        self.neoforge_loader_to_download = get_loaders(
+                            &self.neoforge_versions_download_list,
+                            &self.version_to_download,
+                        )
+                        .first()
+                        .cloned();
+                    }
+                    Err(err) => self.download_text = err,
+                }
 
-                let index = self.downloaders.len() - 1;
-                self.downloaders[index].start(version, ver_type);
+                Command::none()
+            }
+            Message::NeoForgeLoaderToDownloadChanged(new_loader) => {
+                self.neoforge_loader_to_download = Some(new_loader);
+                Command::none()
+            }
+            Message::InstallVersion(ver_type) => {
+                let version = self.version_to_download.clone();
+                let mut downloader = Downloader::new(self.next_downloader_id());
+                downloader.queue(
+                    version,
+                    ver_type,
+                    self.concurrent_downloads,
+                    self.endpoints.clone(),
+                );
+                self.download_text = format!("{} was added to the downloads.", downloader.name());
+                self.add_job(downloader);
                 Command::none()
             }
             Message::JvmNameToAddChanged(name) => {
@@ -618,89 +1172,174 @@ impl Application for Siglauncher {
                 }
                 Command::none()
             }
+            Message::VersionToUninstallChanged(version) => {
+                self.version_to_uninstall = version.clone();
+                self.uninstall_plan = None;
+                self.uninstall_text = format!("Looking for files only {} uses.", version);
+                Command::perform(uninstall::plan_uninstall(version.clone()), move |result| {
+                    Message::GotUninstallPlan(version.clone(), result)
+                })
+            }
+            Message::GotUninstallPlan(version, result) => {
+                // the selection could have changed while this one was being worked out.
+                if version == self.version_to_uninstall {
+                    match result {
+                        Ok(cleanup) => {
+                            self.uninstall_text = format!(
+                                "Uninstalling {} removes {} files and folders, freeing {}.",
+                                version,
+                                cleanup.paths.len(),
+                                size_text(cleanup.freed_bytes())
+                            );
+                            self.uninstall_plan = Some(cleanup)
+                        }
+                        Err(e) => {
+                            self.uninstall_text = format!("Can't uninstall {}: {}", version, e)
+                        }
+                    }
+                }
+                Command::none()
+            }
+            Message::UninstallVersion => {
+                // files a download or the game is using could be removed.
+                if self.is_busy() {
+                    self.uninstall_text = String::from(
+                        "Wait for the game to close and the downloads to finish before uninstalling.",
+                    );
+                    return Command::none();
+                }
+                match self.uninstall_plan.take() {
+                    Some(cleanup) => {
+                        let version = cleanup.version;
+                        self.uninstall_text = format!("Uninstalling {}.", version);
+                        Command::perform(uninstall::uninstall(version.clone()), move |result| {
+                            Message::Uninstalled(version.clone(), result)
+                        })
+                    }
+                    None => Command::none(),
+                }
+            }
+            Message::Uninstalled(version, result) => {
+                match result {
+                    Ok(freed_bytes) => {
+                        self.uninstall_text = format!(
+                            "{} was uninstalled, {} were freed.",
+                            version,
+                            size_text(freed_bytes)
+                        );
+                        self.version_to_uninstall = String::new();
+                        if self.current_version == version {
+                            self.current_version = String::new()
+                        }
+                    }
+                    Err(e) => {
+                        self.uninstall_text = format!("Failed to uninstall {}: {}", version, e)
+                    }
+                }
+                Command::perform(launcher::getinstalledversions(), Message::LoadVersionList)
+            }
+            Message::GotDiskUsage(result) => {
+                match result {
+                    Ok(usage) => {
+                        self.disk_usage_text = format!(
+                            "{} in {}",
+                            size_text(usage.total),
+                            launcher::get_minecraft_dir()
+                        );
+                        self.disk_usage = Some(usage)
+                    }
+                    Err(e) => self.disk_usage_text = format!("Failed to get disk usage: {}", e),
+                }
+                Command::none()
+            }
             Message::GithubButtonPressed => {
                 open::that("https://github.com/JafKc/siglauncher").unwrap();
                 Command::none()
             }
 
             Message::ManageDownload((id, progress)) => {
+                let launching = self.launching_downloader(id);
                 match progress {
-                    downloader::Progress::GotDownloadList(file_number) => {
-                        self.download_text =
-                            format!("Downloaded 0 from {} files. (0%)", file_number);
-                        self.files_download_number = file_number;
+                    downloader::Progress::GotDownloadList(stats)
+                    | downloader::Progress::Downloaded(stats) => {
+                        self.set_job_status(
+                            id,
+                            JobStatus::Running(format!("Downloading. {}", stats)),
+                        );
                     }
-                    downloader::Progress::Downloaded(remaining_files_number) => {
-                        let downloaded_files = self.files_download_number - remaining_files_number;
-
-                        let percentage = (downloaded_files as f32
-                            / self.files_download_number as f32
-                            * 100.0) as i32;
-
-                        self.download_text = format!(
-                            "Downloaded {} from {} files. ({}%)",
-                            downloaded_files, self.files_download_number, percentage
+                    downloader::Progress::RunningProcessors(remaining) => {
+                        self.set_job_status(
+                            id,
+                            JobStatus::Running(format!(
+                                "Running the installer. {} steps left.",
+                                remaining
+                            )),
                         );
                     }
                     downloader::Progress::Finished => {
-                        self.download_text = String::from("Version installed successfully.");
-                        for (index, downloader) in self.downloaders.iter().enumerate() {
-                            if downloader.id == id {
-                                self.downloaders.remove(index);
-                                break;
-                            }
+                        self.finish_job(
+                            id,
+                            JobStatus::Finished(String::from("Version installed successfully.")),
+                        );
+                        // so the installer shows it as installed.
+                        return Command::perform(
+                            launcher::getinstalledversions(),
+                            Message::LoadVersionList,
+                        );
+                    }
+                    downloader::Progress::VerificationFailed(error) => {
+                        let error = format!("Downloaded file is corrupted: {error}");
+                        if launching {
+                            self.game_state_text = error.clone();
+                            self.launcher.state = LauncherState::Idle;
                         }
+                        self.finish_job(id, JobStatus::Failed(error));
                     }
                     downloader::Progress::Errored(error) => {
-                        self.download_text = format!("Failed to install: {error}");
-                        for (index, downloader) in self.downloaders.iter().enumerate() {
-                            if downloader.id == id {
-                                self.downloaders.remove(index);
-                                break;
-                            }
+                        if launching {
+                            self.game_state_text = format!("Download failed: {error}");
+                            self.launcher.state = LauncherState::Idle;
                         }
+                        self.finish_job(id, JobStatus::Failed(error));
                     }
-                    downloader::Progress::StartedJavaDownload(size) => {
-                        self.restrict_launch = true;
-                        self.game_state_text = format!("Downloading java. 0 / {size} MiB (0%)");
-                        self.java_download_size = size;
-                    }
-                    downloader::Progress::JavaDownloadProgressed(downloaded, percentage) => {
-                        self.game_state_text = format!(
-                            "Downloading Java. {downloaded} / {} MiB ({percentage}%)",
-                            self.java_download_size
-                        )
+                    downloader::Progress::Cancelled => {
+                        if launching {
+                            self.game_state_text = String::from("Download cancelled.");
+                            self.launcher.state = LauncherState::Idle;
+                        }
+                        self.finish_job(id, JobStatus::Cancelled);
                     }
-                    downloader::Progress::JavaDownloadFinished => {
-                        self.game_state_text = String::from("Extracting Java")
+                    downloader::Progress::StartedJavaDownload(stats)
+                    | downloader::Progress::JavaDownloadProgressed(stats) => {
+                        self.game_state_text = format!("Downloading Java. {}", stats);
+                        self.set_job_status(id, JobStatus::Running(self.game_state_text.clone()));
                     }
-                    downloader::Progress::JavaExtracted => {
+                    downloader::Progress::JavaInstalled => {
                         self.game_state_text = String::from("Java was installed successfully.");
-                        self.restrict_launch = false;
-                        for (index, downloader) in self.downloaders.iter().enumerate() {
-                            if downloader.id == id {
-                                self.downloaders.remove(index);
-                                break;
-                            }
-                        }
+                        self.finish_job(id, JobStatus::Finished(self.game_state_text.clone()));
 
                         self.launch();
                     }
-                    downloader::Progress::MissingFilesDownloadProgressed(missing_files) => {
-                        self.restrict_launch = true;
-                        self.game_state_text =
-                            format!("Downloading missing files. {} left", missing_files);
+                    downloader::Progress::MissingFilesDownloadProgressed(stats) => {
+                        self.game_state_text = format!("Downloading missing files. {}", stats);
+                        self.set_job_status(id, JobStatus::Running(self.game_state_text.clone()));
                     }
                     downloader::Progress::MissingFilesDownloadFinished => {
-                        self.restrict_launch = false;
-                        for (index, downloader) in self.downloaders.iter().enumerate() {
-                            if downloader.id == id {
-                                self.downloaders.remove(index);
-                                break;
-                            }
+                        let repairing = self.downloaders.iter().any(|downloader| {
+                            downloader.id == id
+                                && matches!(downloader.state, DownloaderState::Repairing(..))
+                        });
+                        if repairing {
+                            self.game_state_text = String::from("Installation repaired.");
+                            self.finish_job(id, JobStatus::Finished(self.game_state_text.clone()));
+                        } else {
+                            self.finish_job(
+                                id,
+                                JobStatus::Finished(String::from("Missing files were downloaded.")),
+                            );
+
+                            self.launch();
                         }
-
-                        self.launch();
                     }
                 }
                 Command::none()
@@ -727,7 +1366,7 @@ impl Application for Siglauncher {
         }
     }
 
-    fn view(&self) -> Element<Self::Message> {
+    fn view(&self) -> Element<'_, Self::Message> {
         let sidebar = container(
             column![
                 //main
@@ -838,7 +1477,29 @@ impl Application for Siglauncher {
                                 )
                                 .placeholder("Select a version")
                                 .width(285)
-                                .text_size(15)
+                                .text_size(15),
+                                row![
+                                    pick_list(
+                                        QuickPlayType::ALL.to_vec(),
+                                        Some(self.quick_play_type),
+                                        Message::QuickPlayTypeChanged
+                                    )
+                                    .width(110)
+                                    .text_size(15),
+                                    text_input(
+                                        match self.quick_play_type {
+                                            QuickPlayType::TitleScreen => "",
+                                            QuickPlayType::Server => "host:port",
+                                            QuickPlayType::World => "World folder",
+                                            QuickPlayType::Realm => "Realm id",
+                                        },
+                                        &self.quick_play_target
+                                    )
+                                    .on_input(Message::QuickPlayTargetChanged)
+                                    .size(15)
+                                    .width(165),
+                                ]
+                                .spacing(10)
                             ]
                             .spacing(10)
                         )
@@ -860,13 +1521,27 @@ impl Application for Siglauncher {
                                 .width(200)
                                 .height(32)
                                 .on_press(Message::OpenGameProfileFolder),
-                                button(
-                                    text("Logs")
-                                        .horizontal_alignment(alignment::Horizontal::Center)
-                                )
-                                .width(80)
-                                .height(32)
-                                .on_press(Message::ChangeScreen(Screen::Logs)),
+                                row![
+                                    button(
+                                        text("Logs")
+                                            .horizontal_alignment(alignment::Horizontal::Center)
+                                    )
+                                    .width(95)
+                                    .height(32)
+                                    .on_press(Message::ChangeScreen(Screen::Logs)),
+                                    action(
+                                        button(
+                                            text("Verify").horizontal_alignment(
+                                                alignment::Horizontal::Center
+                                            )
+                                        )
+                                        .width(95)
+                                        .height(32)
+                                        .on_press(Message::VerifyInstallation),
+                                        "Check the files of the selected version and repair them"
+                                    ),
+                                ]
+                                .spacing(10),
                             ]
                             .spacing(10)
                             .align_items(Alignment::Center)
@@ -883,6 +1558,14 @@ impl Application for Siglauncher {
                             .size(18)
                     ]
                     .spacing(10),
+                    column(
+                        self.downloaders
+                            .iter()
+                            .filter(|downloader| downloader.blocks_launch()
+                                && matches!(downloader.status, JobStatus::Running(_)))
+                            .map(job_controls)
+                            .collect()
+                    ),
                 ]
                 .spacing(25)
                 .max_width(800)
@@ -932,7 +1615,27 @@ impl Application for Siglauncher {
                                         .horizontal_alignment(alignment::Horizontal::Center)
                                 )
                                 .height(32)
-                                .on_press(Message::ChangeScreen(Screen::GameProfile))
+                                .on_press(Message::ChangeScreen(Screen::GameProfile)),
+                                row![
+                                    text_input("Width", &self.game_width)
+                                        .on_input(Message::GameWidthChanged)
+                                        .size(15)
+                                        .width(70),
+                                    text("x"),
+                                    text_input("Height", &self.game_height)
+                                        .on_input(Message::GameHeightChanged)
+                                        .size(15)
+                                        .width(70),
+                                    toggler(
+                                        String::new(),
+                                        self.game_fullscreen,
+                                        Message::GameFullscreenChanged
+                                    )
+                                    .width(Length::Shrink),
+                                    text("Fullscreen").size(15),
+                                ]
+                                .spacing(5)
+                                .align_items(Alignment::Center)
                             ]
                             .spacing(10)
                             .max_width(800)
@@ -960,12 +1663,22 @@ impl Application for Siglauncher {
                                     Message::ShowAllVersionsInDownloadListChanged
                                 )
                                 .width(Length::Shrink),
-                                text("Show all versions in installer")
+                                text("Show unstable loader versions")
                                     .horizontal_alignment(alignment::Horizontal::Center)
                             ]
                             .spacing(10),
-                            button("Add wrapper commands")
-                                .on_press(Message::ChangeScreen(Screen::ModifyCommand))
+                            column![
+                                button("Add wrapper commands")
+                                    .on_press(Message::ChangeScreen(Screen::ModifyCommand)),
+                                row![
+                                    button("Uninstall versions")
+                                        .on_press(Message::ChangeScreen(Screen::Uninstall)),
+                                    button("Disk usage")
+                                        .on_press(Message::ChangeScreen(Screen::DiskUsage))
+                                ]
+                                .spacing(10)
+                            ]
+                            .spacing(10)
                         ]
                         .spacing(50)
                     )
@@ -978,66 +1691,223 @@ impl Application for Siglauncher {
             .max_width(800),
 
             Screen::Installation => {
-                column![
-                //installerscreen
-                //title
-                text("Version installer").size(50),
-
-                row![
-                //vanilla
-                container(
-                column![
-                    text("Vanilla"),
-                pick_list(
-                    self.vanilla_versions_download_list.clone(),
-                    Some(self.vanilla_version_to_download.clone()),
-                    Message::VanillaVersionToDownloadChanged,
-                )
-                .placeholder("Select a version")
-                .width(250)
-                .text_size(15),
-                //installbutton
-                button(
-                    text("Install")
-                        .size(25)
-                        .horizontal_alignment(alignment::Horizontal::Center)
-                )
-                .width(250)
-                .height(40)
-                .on_press_maybe(Some(Message::InstallVersion(downloader::VersionType::Vanilla)))
-                .style(theme::Button::Secondary)].spacing(15)).style(theme::Container::BlackContainer).padding(10),
-
-                //fabric
-                container(
-                    column![
-                        text("Fabric"),
-                    pick_list(
-                        self.fabric_versions_download_list.clone(),
-                        Some(self.fabric_version_to_download.clone()),
-                        Message::FabricVersionToDownloadChanged,
+                let selected = self
+                    .version_browser
+                    .iter()
+                    .find(|version| version.id == self.version_to_download);
+
+                let version_list = column(
+                    self.shown_versions()
+                        .into_iter()
+                        .map(|version| {
+                            button(
+                                row![
+                                    text(&version.id).size(15).width(Length::Fill),
+                                    text(version_type_name(&version.version_type))
+                                        .size(15)
+                                        .width(65),
+                                    text(version.release_date()).size(15).width(85),
+                                    text(if self.all_versions.contains(&version.id) {
+                                        "Installed"
+                                    } else {
+                                        ""
+                                    })
+                                    .size(15)
+                                    .width(65),
+                                ]
+                                .spacing(10),
+                            )
+                            .width(Length::Fill)
+                            .on_press(Message::VersionToDownloadChanged(version.id.clone()))
+                            .style(if version.id == self.version_to_download {
+                                theme::Button::Primary
+                            } else {
+                                theme::Button::Transparent
+                            })
+                            .into()
+                        })
+                        .collect(),
+                );
+
+                let loader_versions: Element<'_, Message> = match self.loader_to_install {
+                    Loader::Vanilla => text("").into(),
+                    Loader::Fabric => pick_list(
+                        self.fabric_loader_download_list.clone(),
+                        self.fabric_loader_to_download.clone(),
+                        Message::FabricLoaderToDownloadChanged,
                     )
-                    .placeholder("Select a version")
-                    .width(250)
-                    .text_size(15),
-                    //installbutton
-                    button(
-                        text("Install")
-                            .size(25)
-                            .horizontal_alignment(alignment::Horizontal::Center)
+                    .placeholder("Select a loader version")
+                    .width(200)
+                    .text_size(15)
+                    .into(),
+                    Loader::Quilt => pick_list(
+                        self.quilt_loader_download_list.clone(),
+                        self.quilt_loader_to_download.clone(),
+                        Message::QuiltLoaderToDownloadChanged,
                     )
-                    .width(250)
-                    .height(40)
-                    .on_press_maybe(Some(Message::InstallVersion(downloader::VersionType::Fabric)))
-                    .style(theme::Button::Secondary)].spacing(15)).style(theme::Container::BlackContainer).padding(10)].spacing(15),
-
-                if !self.show_all_versions_in_download_list{
-                    text("Enable the \"Show all versions in installer\" setting to download snapshots and other versions.").style(theme::Text::Green)
-                } else{
-                    text("")
-                },
-                text(&self.download_text).size(15)]
-            .spacing(15)
-            .max_width(800)
+                    .placeholder("Select a loader version")
+                    .width(200)
+                    .text_size(15)
+                    .into(),
+                    Loader::Forge => pick_list(
+                        get_loaders(
+                            &self.forge_versions_download_list,
+                            &self.version_to_download,
+                        ),
+                        self.forge_loader_to_download.clone(),
+                        Message::ForgeLoaderToDownloadChanged,
+                    )
+                    .placeholder("Select a loader version")
+                    .width(200)
+                    .text_size(15)
+                    .into(),
+                    Loader::NeoForge => pick_list(
+                        get_loaders(
+                            &self.neoforge_versions_download_list,
+                            &self.version_to_download,
+                        ),
+                        self.neoforge_loader_to_download.clone(),
+                        Message::NeoForgeLoaderToDownloadChanged,
+                    )
+                    .placeholder("Select a loader version")
+                    .width(200)
+                    .text_size(15)
+                    .into(),
+                };
+
+                let install_message = match selected {
+                    Some(version) if self.has_loader(version, self.loader_to_install) => match self
+                        .loader_to_install
+                    {
+                        Loader::Vanilla => {
+                            Some(Message::InstallVersion(downloader::VersionType::Vanilla))
+                        }
+                        Loader::Fabric => self.fabric_loader_to_download.as_ref().map(|loader| {
+                            Message::InstallVersion(downloader::VersionType::Fabric(
+                                loader.version.clone(),
+                            ))
+                        }),
+                        Loader::Quilt => self.quilt_loader_to_download.as_ref().map(|loader| {
+                            Message::InstallVersion(downloader::VersionType::Quilt(
+                                loader.version.clone(),
+                            ))
+                        }),
+                        Loader::Forge => self.forge_loader_to_download.as_ref().map(|loader| {
+                            Message::InstallVersion(downloader::VersionType::Forge(
+                                loader.version.clone(),
+                            ))
+                        }),
+                        Loader::NeoForge => {
+                            self.neoforge_loader_to_download.as_ref().map(|loader| {
+                                Message::InstallVersion(downloader::VersionType::NeoForge(
+                                    loader.version.clone(),
+                                ))
+                            })
+                        }
+                    },
+                    _ => None,
+                };
+
+                let details = match selected {
+                    Some(version) => column![
+                        text(&version.id).size(25),
+                        text(format!(
+                            "{}, released {}",
+                            version_type_name(&version.version_type),
+                            version.release_date()
+                        ))
+                        .size(15),
+                        text(match self.required_java_versions.get(&version.id) {
+                            Some(java) => format!("Needs Java {}", java),
+                            None => String::from("Checking the Java version it needs."),
+                        })
+                        .size(15),
+                        text(if self.all_versions.contains(&version.id) {
+                            "Installed"
+                        } else {
+                            "Not installed"
+                        })
+                        .size(15)
+                        .style(theme::Text::Peach),
+                    ],
+                    None => column![text("Select a version").size(25)],
+                };
+
+                column![
+                    //installerscreen
+                    //title
+                    text("Version installer").size(50),
+                    //search and filters
+                    row![
+                        text_input("Search", &self.version_search)
+                            .on_input(Message::VersionSearchChanged)
+                            .size(15)
+                            .width(Length::Fill),
+                        row(VERSION_TYPES
+                            .iter()
+                            .map(|(version_type, name)| {
+                                button(text(name).size(15))
+                                    .on_press(Message::VersionTypeToggled(version_type))
+                                    .style(if self.version_types_shown.contains(version_type) {
+                                        theme::Button::Primary
+                                    } else {
+                                        theme::Button::Secondary
+                                    })
+                                    .into()
+                            })
+                            .collect())
+                        .spacing(5),
+                        pick_list(
+                            Loader::ALL.to_vec(),
+                            Some(self.loader_to_install),
+                            Message::LoaderToInstallChanged
+                        )
+                        .width(110)
+                        .text_size(15),
+                    ]
+                    .spacing(10)
+                    .align_items(Alignment::Center),
+                    row![
+                        container(scrollable(version_list))
+                            .style(theme::Container::BlackContainer)
+                            .width(Length::Fill)
+                            .height(Length::Fill)
+                            .padding(5),
+                        container(
+                            column![
+                                details.spacing(5),
+                                loader_versions,
+                                //installbutton
+                                button(
+                                    text("Install")
+                                        .size(25)
+                                        .horizontal_alignment(alignment::Horizontal::Center)
+                                )
+                                .width(200)
+                                .height(40)
+                                .on_press_maybe(install_message)
+                                .style(theme::Button::Secondary),
+                            ]
+                            .spacing(15)
+                        )
+                        .style(theme::Container::BlackContainer)
+                        .width(220)
+                        .height(Length::Fill)
+                        .padding(10),
+                    ]
+                    .spacing(15)
+                    .height(Length::Fill),
+                    row![
+                        text(&self.download_text).size(15).width(Length::Fill),
+                        button(text("Downloads").size(15))
+                            .on_press(Message::ChangeScreen(Screen::Downloads))
+                            .style(theme::Button::Secondary),
+                    ]
+                    .spacing(10)
+                    .align_items(Alignment::Center)
+                ]
+                .spacing(15)
+                .max_width(800)
             }
 
             Screen::Java => column![
@@ -1114,6 +1984,135 @@ impl Application for Siglauncher {
                     .padding(10)
             ]
             .spacing(15),
+            Screen::Downloads => column![
+                row![
+                    text("Downloads").size(50).width(Length::Fill),
+                    button(text("Clear finished").size(15))
+                        .on_press(Message::ClearFinishedDownloads)
+                        .style(theme::Button::Secondary),
+                ]
+                .align_items(Alignment::Center),
+                scrollable(
+                    column(
+                        self.downloaders
+                            .iter()
+                            .map(|downloader| container(
+                                row![
+                                    column![
+                                        text(downloader.name()).size(20),
+                                        text(downloader.status_text()).size(15),
+                                    ]
+                                    .spacing(5)
+                                    .width(Length::Fill),
+                                    job_controls(downloader),
+                                ]
+                                .spacing(10)
+                                .align_items(Alignment::Center)
+                            )
+                            .style(theme::Container::BlackContainer)
+                            .padding(10)
+                            .into())
+                            .collect()
+                    )
+                    .spacing(10)
+                )
+                .height(Length::Fill),
+                if self.downloaders.is_empty() {
+                    text("Nothing was downloaded yet.")
+                } else {
+                    text("")
+                },
+            ]
+            .spacing(15),
+            Screen::Uninstall => {
+                let mut paths: Vec<String> = self
+                    .uninstall_plan
+                    .iter()
+                    .flat_map(|cleanup| &cleanup.paths)
+                    .take(100)
+                    .map(|(path, _)| path.to_string_lossy().to_string())
+                    .collect();
+                if let Some(cleanup) = &self.uninstall_plan {
+                    if cleanup.paths.len() > paths.len() {
+                        paths.push(format!("and {} more.", cleanup.paths.len() - paths.len()))
+                    }
+                }
+
+                column![
+                    text("Uninstall versions").size(50),
+                    row![
+                        pick_list(
+                            &self.all_versions,
+                            Some(self.version_to_uninstall.clone()),
+                            Message::VersionToUninstallChanged,
+                        )
+                        .placeholder("Select a version")
+                        .width(285)
+                        .text_size(15),
+                        button(text("Uninstall").size(15))
+                            .on_press_maybe(
+                                self.uninstall_plan
+                                    .as_ref()
+                                    .map(|_| Message::UninstallVersion)
+                            )
+                            .style(theme::Button::Secondary),
+                    ]
+                    .spacing(10)
+                    .align_items(Alignment::Center),
+                    text(&self.uninstall_text)
+                        .style(theme::Text::Green)
+                        .size(15),
+                    container(scrollable(text(paths.join("\n")).size(10)))
+                        .style(theme::Container::BlackContainer)
+                        .width(Length::Fill)
+                        .height(Length::Fill)
+                        .padding(10)
+                ]
+                .spacing(15)
+            }
+            Screen::DiskUsage => {
+                let mut sections = vec![];
+                if let Some(usage) = &self.disk_usage {
+                    sections.push(disk_usage_section(
+                        "Versions",
+                        usage.versions.iter().map(|(_, size)| size).sum(),
+                        &usage.versions,
+                    ));
+                    sections.push(disk_usage_section("Libraries", usage.libraries, &[]));
+                    sections.push(disk_usage_section("Assets", usage.assets, &[]));
+                    sections.push(disk_usage_section(
+                        "Java runtimes",
+                        usage.java_runtimes.iter().map(|(_, size)| size).sum(),
+                        &usage.java_runtimes,
+                    ));
+                    sections.push(disk_usage_section(
+                        "Game profiles",
+                        usage.profiles.iter().map(|(_, size)| size).sum(),
+                        &usage.profiles,
+                    ));
+                    sections.push(disk_usage_section("Logs", usage.logs, &[]));
+                    sections.push(disk_usage_section(
+                        "Crash reports",
+                        usage.crash_reports,
+                        &[],
+                    ));
+                }
+
+                column![
+                    row![
+                        text("Disk usage").size(50).width(Length::Fill),
+                        button(text("Refresh").size(15))
+                            .on_press(Message::ChangeScreen(Screen::DiskUsage))
+                            .style(theme::Button::Secondary),
+                    ]
+                    .align_items(Alignment::Center),
+                    text(&self.disk_usage_text)
+                        .style(theme::Text::Green)
+                        .size(15),
+                    scrollable(column(sections).spacing(10)).height(Length::Fill),
+                ]
+                .spacing(15)
+            }
             Screen::ModifyCommand => column![
                 text("Modify game command").size(50),
                 text("advanced settings, only edit if you know what you are doing.")
@@ -1161,6 +2160,70 @@ fn action<'a>(widget: Button<'a, Message, Renderer>, tp_text: &str) -> Element<'
         .into()
 }
 
+fn version_type_name(version_type: &str) -> &str {
+    VERSION_TYPES
+        .iter()
+        .find(|(t, _)| *t == version_type)
+        .map(|(_, name)| *name)
+        .unwrap_or(version_type)
+}
+
+// A part of the minecraft folder on the disk usage screen, with what is inside of it.
+fn disk_usage_section<'a>(
+    name: &str,
+    size: u64,
+    entries: &[(String, u64)],
+) -> Element<'a, Message> {
+    let mut section = column![row![
+        text(name).size(20).width(Length::Fill),
+        text(size_text(size)).size(20)
+    ]]
+    .spacing(5);
+    for (entry, size) in entries {
+        section = section.push(row![
+            text(entry).size(15).width(Length::Fill),
+            text(size_text(*size)).size(15)
+        ]);
+    }
+    container(section)
+        .style(theme::Container::BlackContainer)
+        .padding(10)
+        .into()
+}
+
+fn size_text(bytes: u64) -> String {
+    let mib = bytes as f64 / 1048576.0;
+    if mib >= 1024.0 {
+        format!("{:.2} GiB", mib / 1024.0)
+    } else {
+        format!("{:.1} MiB", mib)
+    }
+}
+
+// Pause and cancel buttons of a job, depending on what it is doing.
+fn job_controls(downloader: &Downloader) -> Element<'_, Message> {
+    let mut controls = row![].spacing(10).align_items(Alignment::Center);
+    if let JobStatus::Running(_) = downloader.status {
+        controls = controls.push(if downloader.control.is_paused() {
+            button(text("Resume").size(15))
+                .on_press(Message::ResumeDownload(downloader.id))
+                .style(theme::Button::Secondary)
+        } else {
+            button(text("Pause").size(15))
+                .on_press(Message::PauseDownload(downloader.id))
+                .style(theme::Button::Secondary)
+        })
+    }
+    if let JobStatus::Queued | JobStatus::Running(_) = downloader.status {
+        controls = controls.push(
+            button(text("Cancel").size(15))
+                .on_press(Message::CancelDownload(downloader.id))
+                .style(theme::Button::Secondary),
+        )
+    }
+    controls.into()
+}
+
 // Configuration file options{
 fn checksettingsfile() {
     let mut conf_json = match Path::new(&get_config_file_path()).exists() {
@@ -1232,6 +2295,27 @@ fn checksettingsfile() {
                 serde_json::to_value(false).unwrap(),
             );
         }
+
+        if !map.contains_key("concurrent_downloads") {
+            map.insert(
+                "concurrent_downloads".to_owned(),
+                serde_json::to_value(downloader::DEFAULT_CONCURRENT_DOWNLOADS).unwrap(),
+            );
+        }
+
+        if !map.contains_key("endpoints") {
+            map.insert(
+                "endpoints".to_owned(),
+                serde_json::to_value(downloader::Endpoints::default()).unwrap(),
+            );
+        }
+
+        if !map.contains_key("network") {
+            map.insert(
+                "network".to_owned(),
+                serde_json::to_value(network::NetworkSettings::default()).unwrap(),
+            );
+        }
     }
     let serializedjson = serde_json::to_string_pretty(&conf_json).unwrap();
 
@@ -1331,15 +2415,35 @@ impl Launcher {
 }
 
 // Downloader struct for subscriptions and interacting with downloader.rs
+// Each downloader is a job in the downloads screen. Its state says what to download, and it only
+// gets a subscription while it is running.
 struct Downloader {
     state: DownloaderState,
     id: usize,
+    control: downloader::Control,
+    status: JobStatus,
+}
+#[derive(Debug, Clone, PartialEq)]
+enum JobStatus {
+    Queued,
+    // with the latest progress.
+    Running(String),
+    Finished(String),
+    Failed(String),
+    Cancelled,
 }
 enum DownloaderState {
     Idle,
-    Downloading(String, downloader::VersionType),
-    JavaDownloading(downloader::Java),
+    Downloading(
+        String,
+        downloader::VersionType,
+        usize,
+        downloader::Endpoints,
+    ),
+    JavaDownloading(String, usize, downloader::Endpoints),
     DownloadingMissingFiles(downloader::DownloadList),
+    // files of a version that failed verification.
+    Repairing(String, downloader::DownloadList),
 }
 
 impl Default for Downloader {
@@ -1347,6 +2451,8 @@ impl Default for Downloader {
         Downloader {
             state: DownloaderState::Idle,
             id: 0,
+            control: downloader::Control::default(),
+            status: JobStatus::Queued,
         }
     }
 }
@@ -1355,33 +2461,126 @@ impl Downloader {
         Downloader {
             state: DownloaderState::Idle,
             id,
+            control: downloader::Control::default(),
+            status: JobStatus::Queued,
+        }
+    }
+
+    pub fn status_text(&self) -> String {
+        match &self.status {
+            JobStatus::Queued => String::from("Queued."),
+            JobStatus::Running(progress) if self.control.is_paused() => {
+                format!("Paused. {}", progress)
+            }
+            JobStatus::Running(progress) | JobStatus::Finished(progress) => progress.clone(),
+            JobStatus::Failed(error) => format!("Failed: {}", error),
+            JobStatus::Cancelled => String::from("Cancelled."),
+        }
+    }
+
+    // Shown next to the pause and cancel buttons.
+    pub fn name(&self) -> String {
+        match &self.state {
+            DownloaderState::Idle => String::new(),
+            DownloaderState::Downloading(version, version_type, _, _) => {
+                version_type.version_name(version)
+            }
+            DownloaderState::JavaDownloading(component, _, _) => format!("Java ({})", component),
+            DownloaderState::DownloadingMissingFiles(_) => String::from("Missing files"),
+            DownloaderState::Repairing(version, _) => format!("Repairing {}", version),
         }
     }
 
-    pub fn start(&mut self, version: String, version_type: downloader::VersionType) {
-        self.state = DownloaderState::Downloading(version, version_type)
+    pub fn blocks_launch(&self) -> bool {
+        matches!(
+            self.state,
+            DownloaderState::JavaDownloading(..)
+                | DownloaderState::DownloadingMissingFiles(_)
+                | DownloaderState::Repairing(..)
+        )
+    }
+
+    pub fn queue(
+        &mut self,
+        version: String,
+        version_type: downloader::VersionType,
+        concurrent_downloads: usize,
+        endpoints: downloader::Endpoints,
+    ) {
+        self.state =
+            DownloaderState::Downloading(version, version_type, concurrent_downloads, endpoints)
+    }
+    pub fn queue_java(
+        &mut self,
+        component: String,
+        concurrent_downloads: usize,
+        endpoints: downloader::Endpoints,
+    ) {
+        self.state = DownloaderState::JavaDownloading(component, concurrent_downloads, endpoints)
+    }
+    pub fn queue_missing_files(
+        &mut self,
+        files: Vec<downloader::Download>,
+        concurrent_downloads: usize,
+    ) {
+        self.state = DownloaderState::DownloadingMissingFiles(
+            self.download_list(files, concurrent_downloads),
+        )
     }
-    pub fn start_java(&mut self, java: downloader::Java) {
-        self.state = DownloaderState::JavaDownloading(java)
+    // Downloads the files of a version that failed verification again, without launching it.
+    pub fn queue_repair(
+        &mut self,
+        version: String,
+        files: Vec<downloader::Download>,
+        concurrent_downloads: usize,
+    ) {
+        self.state =
+            DownloaderState::Repairing(version, self.download_list(files, concurrent_downloads))
     }
-    pub fn start_missing_files(&mut self, files: Vec<downloader::Download>) {
-        let download_list = downloader::DownloadList {
+    fn download_list(
+        &self,
+        files: Vec<downloader::Download>,
+        concurrent_downloads: usize,
+    ) -> downloader::DownloadList {
+        downloader::DownloadList {
             download_list: files,
-            client: reqwest::Client::new(),
-        };
-        self.state = DownloaderState::DownloadingMissingFiles(download_list)
+            client: network::client(),
+            concurrent_downloads,
+            control: self.control.clone(),
+        }
     }
     pub fn subscription(&self) -> Subscription<Message> {
+        if !matches!(self.status, JobStatus::Running(_)) {
+            return Subscription::none();
+        }
         match &self.state {
             DownloaderState::Idle => Subscription::none(),
-            DownloaderState::Downloading(version, version_type) => {
-                downloader::start(self.id, version.to_string(), version_type.clone())
-                    .map(Message::ManageDownload)
-            }
-            DownloaderState::JavaDownloading(java) => {
-                downloader::start_java(self.id, java.clone()).map(Message::ManageDownload)
+            DownloaderState::Downloading(
+                version,
+                version_type,
+                concurrent_downloads,
+                endpoints,
+            ) => downloader::start(
+                self.id,
+                version.to_string(),
+                version_type.clone(),
+                *concurrent_downloads,
+                endpoints.clone(),
+                self.control.clone(),
+            )
+            .map(Message::ManageDownload),
+            DownloaderState::JavaDownloading(component, concurrent_downloads, endpoints) => {
+                downloader::start_java(
+                    self.id,
+                    component.clone(),
+                    *concurrent_downloads,
+                    endpoints.clone(),
+                    self.control.clone(),
+                )
+                .map(Message::ManageDownload)
             }
-            DownloaderState::DownloadingMissingFiles(download_list) => {
+            DownloaderState::DownloadingMissingFiles(download_list)
+            | DownloaderState::Repairing(_, download_list) => {
                 downloader::start_missing_files(self.id, download_list.clone())
                     .map(Message::ManageDownload)
             }
@@ -1404,6 +2603,60 @@ struct Java {
     flags: String,
 }
 
+// Window settings of a game profile, stored in the profiles section of the settings file. The
+// game picks its own window size when there is no width or height.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+struct ProfileSettings {
+    width: Option<u32>,
+    height: Option<u32>,
+    fullscreen: bool,
+    quick_play: Option<launcher::QuickPlay>,
+}
+
+fn get_quick_play_fields(settings: &ProfileSettings) -> (QuickPlayType, String) {
+    match &settings.quick_play {
+        Some(launcher::QuickPlay::Multiplayer(address)) => (QuickPlayType::Server, address.clone()),
+        Some(launcher::QuickPlay::Singleplayer(world)) => (QuickPlayType::World, world.clone()),
+        Some(launcher::QuickPlay::Realms(realm)) => (QuickPlayType::Realm, realm.clone()),
+        None => (QuickPlayType::TitleScreen, String::new()),
+    }
+}
+
+fn get_profile_settings(profile: &str) -> ProfileSettings {
+    serde_json::from_value(getjson(get_config_file_path())["profiles"][profile].clone())
+        .unwrap_or_default()
+}
+
+fn updateprofilesettingsfile(profile: &str, settings: &ProfileSettings) -> std::io::Result<()> {
+    let mut data = getjson(get_config_file_path());
+    if !data["profiles"].is_object() {
+        data["profiles"] = serde_json::json!({});
+    }
+    data["profiles"][profile] = serde_json::to_value(settings)?;
+
+    let serialized = serde_json::to_string_pretty(&data)?;
+
+    let mut file = OpenOptions::new()
+        .write(true)
+        .truncate(true)
+        .open(get_config_file_path())?;
+    file.write_all(serialized.as_bytes())?;
+
+    Ok(())
+}
+
+// Loader versions available for a game version.
+fn get_loaders(
+    list: &[(String, Vec<downloader::LoaderVersion>)],
+    game_version: &str,
+) -> Vec<downloader::LoaderVersion> {
+    list.iter()
+        .find(|(game, _)| game == game_version)
+        .map(|(_, loaders)| loaders.clone())
+        .unwrap_or_default()
+}
+
 fn getjson(jpathstring: String) -> Value {
     let jsonpath = Path::new(&jpathstring);
 
@@ -0,0 +1,105 @@
+use reqwest::{Client, Proxy};
+use serde::{Deserialize, Serialize};
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex, RwLock,
+    },
+    time::{Duration, Instant},
+};
+
+// Proxy, timeouts and download speed limit, stored in the settings file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NetworkSettings {
+    // like http://proxy.example.com:8080, empty to connect directly.
+    pub proxy: String,
+    pub proxy_username: String,
+    pub proxy_password: String,
+    // seconds.
+    pub connect_timeout: u64,
+    // seconds to wait for the server to send something before giving up.
+    pub read_timeout: u64,
+    // shared by every download, 0 for no limit.
+    pub max_bytes_per_second: u64,
+}
+
+impl Default for NetworkSettings {
+    fn default() -> Self {
+        NetworkSettings {
+            proxy: String::new(),
+            proxy_username: String::new(),
+            proxy_password: String::new(),
+            connect_timeout: 10,
+            read_timeout: 30,
+            max_bytes_per_second: 0,
+        }
+    }
+}
+
+static CLIENT: RwLock<Option<Client>> = RwLock::new(None);
+static READ_TIMEOUT: AtomicU64 = AtomicU64::new(30);
+static MAX_BYTES_PER_SECOND: AtomicU64 = AtomicU64::new(0);
+// when the bytes downloaded so far are paid off under the speed limit.
+static NEXT_SLOT: Mutex<Option<Instant>> = Mutex::new(None);
+
+// Builds the client every request goes through. Until this is called, requests use one with the
+// default settings.
+pub fn configure(settings: &NetworkSettings) -> Result<(), String> {
+    let mut builder =
+        Client::builder().connect_timeout(Duration::from_secs(settings.connect_timeout.max(1)));
+    if !settings.proxy.is_empty() {
+        let mut proxy = match Proxy::all(&settings.proxy) {
+            Ok(ok) => ok,
+            Err(e) => return Err(format!("invalid proxy {}: {}", settings.proxy, e)),
+        };
+        if !settings.proxy_username.is_empty() {
+            proxy = proxy.basic_auth(&settings.proxy_username, &settings.proxy_password);
+        }
+        builder = builder.proxy(proxy);
+    }
+    let client = match builder.build() {
+        Ok(ok) => ok,
+        Err(e) => return Err(e.to_string()),
+    };
+
+    *CLIENT.write().unwrap() = Some(client);
+    READ_TIMEOUT.store(settings.read_timeout.max(1), Ordering::Relaxed);
+    MAX_BYTES_PER_SECOND.store(settings.max_bytes_per_second, Ordering::Relaxed);
+    Ok(())
+}
+
+// Clients share their connection pool when cloned, so this is cheap.
+pub fn client() -> Client {
+    if let Some(client) = CLIENT.read().unwrap().as_ref() {
+        return client.clone();
+    }
+    if let Err(e) = configure(&NetworkSettings::default()) {
+        println!("Failed to create the http client: {}", e)
+    }
+    CLIENT.read().unwrap().clone().unwrap_or_default()
+}
+
+// For small requests like jsons this is how long the whole response can take, downloads apply it
+// to every chunk they receive.
+pub fn read_timeout() -> Duration {
+    Duration::from_secs(READ_TIMEOUT.load(Ordering::Relaxed))
+}
+
+// Called with the size of every chunk a download receives, it waits for as long as those bytes
+// take at the speed limit. Not reading the response in the meantime slows the server down too.
+pub async fn throttle(bytes: u64) {
+    let max_bytes_per_second = MAX_BYTES_PER_SECOND.load(Ordering::Relaxed);
+    if max_bytes_per_second == 0 {
+        return;
+    }
+    let wait = {
+        let mut next_slot = NEXT_SLOT.lock().unwrap();
+        let now = Instant::now();
+        let start = next_slot.filter(|slot| *slot > now).unwrap_or(now);
+        let end = start + Duration::from_secs_f64(bytes as f64 / max_bytes_per_second as f64);
+        *next_slot = Some(end);
+        end - now
+    };
+    tokio::time::sleep(wait).await;
+}
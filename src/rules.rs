@@ -0,0 +1,355 @@
+use regex::Regex;
+use serde_json::Value;
+use std::{
+    collections::HashMap,
+    process::Command,
+    sync::{Mutex, OnceLock},
+};
+
+// Libraries and arguments in version jsons can have a list of rules. Nothing is allowed until a
+// rule allows it, and every rule that matches this system overrides the ones before it, so a
+// later disallow can take back an earlier allow.
+pub fn is_allowed(rules: &Value, features: &[&str]) -> bool {
+    let rules = match rules.as_array() {
+        Some(rules) => rules,
+        None => return true,
+    };
+
+    let mut allowed = false;
+    for rule in rules {
+        if matches_os(&rule["os"]) && matches_features(&rule["features"], features) {
+            allowed = rule["action"] == "allow";
+        }
+    }
+    allowed
+}
+
+fn matches_os(os: &Value) -> bool {
+    if let Some(name) = os["name"].as_str() {
+        if name != get_os_name() {
+            return false;
+        }
+    }
+    if let Some(arch) = os["arch"].as_str() {
+        if arch != get_arch() {
+            return false;
+        }
+    }
+    if let Some(version) = os["version"].as_str() {
+        match get_version_regex(version) {
+            Some(version) => {
+                if !version.is_match(get_os_version()) {
+                    return false;
+                }
+            }
+            None => return false,
+        }
+    }
+    true
+}
+
+// The same rules are checked for every library of every version, so each os version pattern is
+// only compiled once.
+fn get_version_regex(pattern: &str) -> Option<Regex> {
+    static VERSION_REGEXES: OnceLock<Mutex<HashMap<String, Option<Regex>>>> = OnceLock::new();
+    let mut regexes = VERSION_REGEXES
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap();
+    regexes
+        .entry(pattern.to_string())
+        .or_insert_with(|| match Regex::new(pattern) {
+            Ok(ok) => Some(ok),
+            Err(e) => {
+                println!("Invalid os version rule {}: {}", pattern, e);
+                None
+            }
+        })
+        .clone()
+}
+
+// Features like is_demo_user or has_custom_resolution, a rule only matches when every feature it
+// lists is turned on or off like it asks.
+fn matches_features(required: &Value, features: &[&str]) -> bool {
+    match required.as_object() {
+        Some(required) => required
+            .iter()
+            .all(|(feature, value)| features.contains(&feature.as_str()) == (value == true)),
+        None => true,
+    }
+}
+
+// Whether a list of rules turns something on or off with a feature, which is how version jsons
+// tell what the game supports, like is_quick_play_multiplayer since 1.20.
+pub fn uses_feature(rules: &Value, feature: &str) -> bool {
+    rules
+        .as_array()
+        .into_iter()
+        .flatten()
+        .any(|rule| rule["features"].get(feature).is_some())
+}
+
+// Name mojang uses for this system.
+pub fn get_os_name() -> &'static str {
+    match std::env::consts::OS {
+        "macos" => "osx",
+        os => os,
+    }
+}
+
+pub fn get_arch() -> &'static str {
+    match std::env::consts::ARCH {
+        "aarch64" => "arm64",
+        arch => arch,
+    }
+}
+
+// Kernel release on linux (like 6.5.0-14-generic) and the version number on windows
+// (like 10.0.19045.3570), which is what the os version rules are written against.
+fn get_os_version() -> &'static str {
+    static OS_VERSION: OnceLock<String> = OnceLock::new();
+    OS_VERSION.get_or_init(|| match std::env::consts::OS {
+        "windows" => match Command::new("cmd").args(["/c", "ver"]).output() {
+            Ok(output) => String::from_utf8_lossy(&output.stdout)
+                .split("Version ")
+                .nth(1)
+                .unwrap_or("")
+                .trim()
+                .trim_end_matches(']')
+                .to_string(),
+            Err(_) => String::new(),
+        },
+        _ => std::fs::read_to_string("/proc/sys/kernel/osrelease")
+            .unwrap_or_default()
+            .trim()
+            .to_string(),
+    })
+}
+
+// Suffix LWJGL uses for this architecture in its natives classifiers, x86_64 has none.
+fn get_natives_arch() -> Option<&'static str> {
+    match std::env::consts::ARCH {
+        "x86" => Some("x86"),
+        "aarch64" => Some("arm64"),
+        "arm" => Some("arm32"),
+        _ => None,
+    }
+}
+
+// Libraries of a version json that apply to this system. Since LWJGL 3.3, natives are libraries
+// of their own with the platform in the classifier (org.lwjgl:lwjgl:3.3.1:natives-windows-arm64),
+// and only the ones built for this architecture are kept. Mojang doesn't list natives for arm
+// linux, so those are taken from maven central instead.
+pub fn get_system_libraries(libraries: &[Value]) -> Vec<Value> {
+    let allowed: Vec<&Value> = libraries
+        .iter()
+        .filter(|library| is_allowed(&library["rules"], &[]))
+        .collect();
+    let listed = allowed
+        .iter()
+        .any(|library| get_natives_platform(library) == Some(get_natives_arch()));
+
+    let mut system_libraries = vec![];
+    for library in allowed {
+        match (get_natives_platform(library), get_natives_arch()) {
+            (None, _) => system_libraries.push(library.clone()),
+            (Some(arch), system_arch) if arch == system_arch => {
+                system_libraries.push(library.clone())
+            }
+            // the x86_64 natives stand in for the ones missing from the json. Maven central only
+            // has arm builds, so 32 bit x86 is left without.
+            (Some(None), Some(system_arch)) if !listed && system_arch.starts_with("arm") => {
+                if let Some(library) = with_natives_arch(library, system_arch) {
+                    system_libraries.push(library)
+                }
+            }
+            _ => {}
+        }
+    }
+    system_libraries
+}
+
+// Architecture in the classifier of a natives library for this os, Some(None) being x86_64.
+// None for every other library.
+fn get_natives_platform(library: &Value) -> Option<Option<&str>> {
+    let classifier = library["name"].as_str()?.split(':').nth(3)?;
+    let arch = classifier
+        .strip_prefix("natives-")?
+        .strip_prefix(std::env::consts::OS)?;
+    match arch {
+        "" => Some(None),
+        arch => arch.strip_prefix('-').map(Some),
+    }
+}
+
+fn with_natives_arch(library: &Value, arch: &str) -> Option<Value> {
+    let name = library["name"].as_str()?;
+    if !name.starts_with("org.lwjgl:") {
+        return None;
+    }
+    let name = format!("{}-{}", name, arch);
+    Some(serde_json::json!({
+        "name": name,
+        "downloads": {
+            "artifact": {
+                "url": format!(
+                    "https://repo1.maven.org/maven2/{}",
+                    super::downloader::get_library_path(&name)
+                )
+            }
+        }
+    }))
+}
+
+// Classifier of the natives jar of a library from before LWJGL 3.3, taken from its natives map.
+// There ${arch} stands for the jvm bitness, and a build for this architecture is used when the
+// library has one. On arm there is no falling back to the x86 natives, the game would crash on
+// start with them.
+pub fn get_natives_classifier(library: &Value) -> Result<Option<String>, String> {
+    let bitness = if cfg!(target_pointer_width = "64") {
+        "64"
+    } else {
+        "32"
+    };
+    let classifier = match library["natives"][get_os_name()].as_str() {
+        Some(classifier) => classifier.replace("${arch}", bitness),
+        None => return Ok(None),
+    };
+
+    match get_natives_arch() {
+        Some(arch) => {
+            let arch_classifier = format!("{}-{}", classifier, arch);
+            if library["downloads"]["classifiers"][&arch_classifier].is_object() {
+                Ok(Some(arch_classifier))
+            } else if arch.starts_with("arm") {
+                Err(format!(
+                    "{} has no natives for this architecture ({})",
+                    library["name"].as_str().unwrap_or("a library"),
+                    std::env::consts::ARCH
+                ))
+            } else {
+                Ok(Some(classifier))
+            }
+        }
+        None => Ok(Some(classifier)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn other_os_name() -> &'static str {
+        match get_os_name() {
+            "windows" => "linux",
+            _ => "windows",
+        }
+    }
+
+    #[test]
+    fn no_rules_allow_everything() {
+        assert!(is_allowed(&Value::Null, &[]));
+    }
+
+    #[test]
+    fn rules_deny_by_default() {
+        assert!(!is_allowed(&json!([]), &[]));
+        assert!(!is_allowed(
+            &json!([{ "action": "allow", "os": { "name": other_os_name() } }]),
+            &[]
+        ));
+    }
+
+    #[test]
+    fn last_matching_rule_wins() {
+        let allow_then_disallow = json!([
+            { "action": "allow" },
+            { "action": "disallow", "os": { "name": get_os_name() } }
+        ]);
+        assert!(!is_allowed(&allow_then_disallow, &[]));
+
+        let disallow_then_allow = json!([
+            { "action": "disallow", "os": { "name": get_os_name() } },
+            { "action": "allow" }
+        ]);
+        assert!(is_allowed(&disallow_then_allow, &[]));
+
+        // rules for other systems don't take anything back.
+        let other_disallow = json!([
+            { "action": "allow" },
+            { "action": "disallow", "os": { "name": other_os_name() } }
+        ]);
+        assert!(is_allowed(&other_disallow, &[]));
+    }
+
+    #[test]
+    fn os_arch_must_match() {
+        assert!(is_allowed(
+            &json!([{ "action": "allow", "os": { "arch": get_arch() } }]),
+            &[]
+        ));
+        assert!(!is_allowed(
+            &json!([{ "action": "allow", "os": { "arch": "no-such-arch" } }]),
+            &[]
+        ));
+    }
+
+    #[test]
+    fn os_version_is_a_regex() {
+        let current = format!("^{}$", regex::escape(get_os_version()));
+        assert!(is_allowed(
+            &json!([{ "action": "allow", "os": { "version": current } }]),
+            &[]
+        ));
+        assert!(is_allowed(
+            &json!([{ "action": "allow", "os": { "version": ".*" } }]),
+            &[]
+        ));
+        assert!(!is_allowed(
+            &json!([{ "action": "allow", "os": { "version": "^no-such-version$" } }]),
+            &[]
+        ));
+        // an invalid pattern never matches.
+        assert!(!is_allowed(
+            &json!([{ "action": "allow", "os": { "version": "(" } }]),
+            &[]
+        ));
+    }
+
+    #[test]
+    fn uses_feature_only_looks_at_rule_features() {
+        let rules = json!([{ "action": "allow", "features": { "is_quick_play_realms": true } }]);
+        assert!(uses_feature(&rules, "is_quick_play_realms"));
+        assert!(!uses_feature(&rules, "is_quick_play_multiplayer"));
+        assert!(!uses_feature(
+            &json!([{ "action": "allow", "os": { "name": "is_quick_play_multiplayer" } }]),
+            "is_quick_play_multiplayer"
+        ));
+        assert!(!uses_feature(&Value::Null, "is_quick_play_multiplayer"));
+    }
+
+    #[test]
+    fn features_must_all_match() {
+        let resolution = json!([{
+            "action": "allow",
+            "features": { "has_custom_resolution": true }
+        }]);
+        assert!(!is_allowed(&resolution, &[]));
+        assert!(is_allowed(&resolution, &["has_custom_resolution"]));
+
+        let not_demo = json!([{ "action": "allow", "features": { "is_demo_user": false } }]);
+        assert!(is_allowed(&not_demo, &[]));
+        assert!(!is_allowed(&not_demo, &["is_demo_user"]));
+
+        let both = json!([{
+            "action": "allow",
+            "features": { "has_custom_resolution": true, "is_quick_play_multiplayer": true }
+        }]);
+        assert!(!is_allowed(&both, &["has_custom_resolution"]));
+        assert!(is_allowed(
+            &both,
+            &["has_custom_resolution", "is_quick_play_multiplayer"]
+        ));
+    }
+}
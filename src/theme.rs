@@ -48,6 +48,7 @@ impl text::StyleSheet for Theme {
 }
 
 #[derive(Debug, Clone, Copy, Default)]
+#[allow(clippy::enum_variant_names)]
 pub enum Container {
     #[default]
     Default,
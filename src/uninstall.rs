@@ -0,0 +1,354 @@
+use super::downloader::{
+    get_asset_downloads, get_library_downloads, get_log_config_path, Endpoints,
+};
+use super::launcher::get_size;
+use super::verify::read_json;
+use std::{
+    collections::HashSet,
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+// Forge and NeoForge installers leave files in these folders that their version json doesn't
+// list, like the patched client jar, so they are kept while one of those versions is installed.
+const LOADER_LIBRARY_FOLDERS: [&str; 3] = [
+    "net/minecraft/client",
+    "net/minecraftforge",
+    "net/neoforged",
+];
+
+// What uninstalling a version removes: its folder, plus the libraries, assets and java runtimes
+// that no other installed version uses.
+#[derive(Debug, Clone, Default)]
+pub struct Cleanup {
+    pub version: String,
+    // files and folders with their size in bytes.
+    pub paths: Vec<(PathBuf, u64)>,
+}
+
+impl Cleanup {
+    pub fn freed_bytes(&self) -> u64 {
+        self.paths.iter().map(|(_, size)| size).sum()
+    }
+}
+
+// Works out what uninstalling a version would remove, without removing anything.
+pub async fn plan_uninstall(version: String) -> Result<Cleanup, String> {
+    match tokio::task::spawn_blocking(move || {
+        get_cleanup(&super::launcher::get_minecraft_dir(), &version)
+    })
+    .await
+    {
+        Ok(result) => result,
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+// Removes a version and the files only it uses, returning how many bytes were freed. The plan
+// shown to the user could be outdated by now, so it is worked out again right before removing.
+pub async fn uninstall(version: String) -> Result<u64, String> {
+    match tokio::task::spawn_blocking(move || {
+        let mc_dir = super::launcher::get_minecraft_dir();
+        let cleanup = get_cleanup(&mc_dir, &version)?;
+        // libraries and assets are stored in nested folders, which go once they are empty.
+        let nested_folders = [
+            Path::new(&mc_dir).join("libraries"),
+            Path::new(&mc_dir).join("assets").join("objects"),
+        ];
+        for (path, _) in &cleanup.paths {
+            let result = if path.is_dir() {
+                fs::remove_dir_all(path)
+            } else {
+                fs::remove_file(path)
+            };
+            match result {
+                Ok(ok) => ok,
+                // something else removed it already.
+                Err(e) if e.kind() == ErrorKind::NotFound => {}
+                Err(e) => return Err(format!("failed to remove {}: {}", path.display(), e)),
+            }
+            if let Some(root) = nested_folders.iter().find(|root| path.starts_with(root)) {
+                remove_empty_folders(path, root)
+            }
+        }
+        Ok(cleanup.freed_bytes())
+    })
+    .await
+    {
+        Ok(result) => result,
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+fn get_cleanup(mc_dir: &str, version: &str) -> Result<Cleanup, String> {
+    let version_dir = Path::new(mc_dir).join("versions").join(version);
+    if version.is_empty() || !version_dir.is_dir() {
+        return Err(format!("{} is not installed", version));
+    }
+
+    let mut cleanup = Cleanup {
+        version: version.to_string(),
+        paths: vec![(version_dir.clone(), get_size(&version_dir))],
+    };
+
+    let mut used_files = HashSet::new();
+    let mut used_indexes = HashSet::new();
+    let mut used_runtimes = HashSet::new();
+    let mut protected_folders = vec![];
+
+    let versions = match fs::read_dir(format!("{}/versions", mc_dir)) {
+        Ok(ok) => ok,
+        Err(e) => return Err(e.to_string()),
+    };
+    for entry in versions.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let json_path = entry.path().join(format!("{}.json", name));
+        if name == version || !json_path.exists() {
+            continue;
+        }
+
+        let version_json = read_json(&json_path.to_string_lossy())?;
+        // nothing can be removed if we don't know everything a version uses.
+        let vanilla_json = match version_json["inheritsFrom"].as_str() {
+            Some(vanilla) => match read_json(
+                &entry
+                    .path()
+                    .join(format!("{}.json", vanilla))
+                    .to_string_lossy(),
+            ) {
+                Ok(ok) => ok,
+                Err(_) => {
+                    return Err(format!(
+                        "{} is missing its vanilla json, launch it once before uninstalling",
+                        name
+                    ))
+                }
+            },
+            None => version_json.clone(),
+        };
+
+        let mut libraries = vanilla_json["libraries"]
+            .as_array()
+            .cloned()
+            .unwrap_or_default();
+        if !version_json["inheritsFrom"].is_null() {
+            libraries.extend(
+                version_json["libraries"]
+                    .as_array()
+                    .cloned()
+                    .unwrap_or_default(),
+            );
+        }
+        let loader_installed = libraries.iter().any(|library| {
+            let name = library["name"].as_str().unwrap_or("");
+            name.starts_with("net.minecraftforge:") || name.starts_with("net.neoforged")
+        });
+        if loader_installed {
+            protected_folders.extend(
+                LOADER_LIBRARY_FOLDERS
+                    .iter()
+                    .map(|folder| Path::new(mc_dir).join("libraries").join(folder)),
+            );
+        }
+
+        match get_library_downloads(
+            &mc_dir.to_string(),
+            &libraries,
+            &entry.path().to_string_lossy().to_string(),
+            &Endpoints::default(),
+        ) {
+            Ok(ok) => used_files.extend(ok.into_iter().map(|file| PathBuf::from(file.path))),
+            Err(e) => return Err(e),
+        }
+
+        if let Some(index) = vanilla_json["assets"].as_str() {
+            used_indexes.insert(index.to_string());
+        }
+        if let Some(log_config) = get_log_config_path(mc_dir, &vanilla_json) {
+            used_files.insert(PathBuf::from(log_config));
+        }
+        used_runtimes.insert(super::java_runtime::get_required_java(&vanilla_json).0);
+    }
+
+    let assets_dir = Path::new(mc_dir).join("assets");
+    for index in &used_indexes {
+        let index_path = assets_dir.join("indexes").join(format!("{}.json", index));
+        used_files.insert(index_path.clone());
+        // assets of an index that is gone are downloaded again on launch.
+        if let Ok(index_json) = read_json(&index_path.to_string_lossy()) {
+            used_files.extend(
+                get_asset_downloads(&mc_dir.to_string(), &index_json, &Endpoints::default())
+                    .into_iter()
+                    .map(|file| PathBuf::from(file.path)),
+            );
+        }
+    }
+
+    for folder in [
+        Path::new(mc_dir).join("libraries"),
+        assets_dir.join("objects"),
+        assets_dir.join("indexes"),
+        assets_dir.join("log_configs"),
+    ] {
+        let mut files = vec![];
+        list_files(&folder, &mut files);
+        for file in files {
+            if !used_files.contains(&file)
+                && !protected_folders
+                    .iter()
+                    .any(|folder| file.starts_with(folder))
+            {
+                let size = get_size(&file);
+                cleanup.paths.push((file, size))
+            }
+        }
+    }
+
+    // copies of the assets made for old versions.
+    if let Ok(entries) = fs::read_dir(assets_dir.join("virtual")) {
+        for entry in entries.flatten() {
+            if !used_indexes.contains(&entry.file_name().to_string_lossy().to_string()) {
+                cleanup.paths.push((entry.path(), get_size(&entry.path())))
+            }
+        }
+    }
+
+    if let Ok(entries) = fs::read_dir(format!("{}/siglauncher_java", mc_dir)) {
+        for entry in entries.flatten() {
+            if !used_runtimes.contains(&entry.file_name().to_string_lossy().to_string()) {
+                cleanup.paths.push((entry.path(), get_size(&entry.path())))
+            }
+        }
+    }
+
+    Ok(cleanup)
+}
+
+fn list_files(folder: &Path, files: &mut Vec<PathBuf>) {
+    if let Ok(entries) = fs::read_dir(folder) {
+        for entry in entries.flatten() {
+            match entry.file_type() {
+                Ok(file_type) if file_type.is_dir() => list_files(&entry.path(), files),
+                Ok(_) => files.push(entry.path()),
+                Err(_) => {}
+            }
+        }
+    }
+}
+
+// Removes the folders a removed file was in, up to `root`, for as long as they are empty.
+fn remove_empty_folders(path: &Path, root: &Path) {
+    let mut folder = path.parent();
+    while let Some(current) = folder {
+        if current == root || !current.starts_with(root) || fs::remove_dir(current).is_err() {
+            break;
+        }
+        folder = current.parent();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    // Makes a minecraft folder with the given versions (name and json) and library files.
+    fn write_minecraft_dir(name: &str, versions: &[(&str, Value)], libraries: &[&str]) -> PathBuf {
+        let mc_dir =
+            std::env::temp_dir().join(format!("siglauncher-test-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&mc_dir);
+        for (version, version_json) in versions {
+            let version_dir = mc_dir.join("versions").join(version);
+            fs::create_dir_all(&version_dir).unwrap();
+            fs::write(
+                version_dir.join(format!("{}.json", version)),
+                version_json.to_string(),
+            )
+            .unwrap();
+        }
+        for library in libraries {
+            let path = mc_dir.join("libraries").join(library);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, b"library").unwrap();
+        }
+        mc_dir
+    }
+
+    fn removed_paths(mc_dir: &Path, version: &str) -> Vec<PathBuf> {
+        get_cleanup(&mc_dir.to_string_lossy(), version)
+            .unwrap()
+            .paths
+            .into_iter()
+            .map(|(path, _)| path)
+            .collect()
+    }
+
+    #[test]
+    fn shared_libraries_are_kept() {
+        let mc_dir = write_minecraft_dir(
+            "uninstall-shared",
+            &[
+                (
+                    "old",
+                    json!({ "libraries": [
+                        { "name": "com.example:shared:1.0" },
+                        { "name": "com.example:only-old:1.0" }
+                    ] }),
+                ),
+                (
+                    "new",
+                    json!({ "libraries": [{ "name": "com.example:shared:1.0" }] }),
+                ),
+            ],
+            &[
+                "com/example/shared/1.0/shared-1.0.jar",
+                "com/example/only-old/1.0/only-old-1.0.jar",
+                // left behind by a version that was removed by hand.
+                "com/example/orphan/1.0/orphan-1.0.jar",
+            ],
+        );
+        let libraries = mc_dir.join("libraries/com/example");
+
+        let removed = removed_paths(&mc_dir, "old");
+
+        assert!(removed.contains(&mc_dir.join("versions/old")));
+        assert!(removed.contains(&libraries.join("only-old/1.0/only-old-1.0.jar")));
+        assert!(removed.contains(&libraries.join("orphan/1.0/orphan-1.0.jar")));
+        assert!(!removed.contains(&libraries.join("shared/1.0/shared-1.0.jar")));
+        assert!(!removed.contains(&mc_dir.join("versions/new")));
+        fs::remove_dir_all(mc_dir).unwrap();
+    }
+
+    #[test]
+    fn forge_files_are_kept_while_forge_is_installed() {
+        let mc_dir = write_minecraft_dir(
+            "uninstall-forge",
+            &[
+                ("other", json!({ "libraries": [] })),
+                (
+                    "1.20.1-forge",
+                    json!({
+                        "inheritsFrom": "1.20.1",
+                        "libraries": [{ "name": "net.minecraftforge:forge:1.20.1-47.2.0" }]
+                    }),
+                ),
+            ],
+            &[
+                "net/minecraftforge/forge/1.20.1-47.2.0/forge-1.20.1-47.2.0-client.jar",
+                "net/minecraft/client/1.20.1/client-1.20.1-srg.jar",
+            ],
+        );
+        // the forge json inherits from the vanilla one next to it.
+        fs::write(
+            mc_dir.join("versions/1.20.1-forge/1.20.1.json"),
+            json!({ "libraries": [] }).to_string(),
+        )
+        .unwrap();
+
+        let removed = removed_paths(&mc_dir, "other");
+
+        assert_eq!(removed, vec![mc_dir.join("versions/other")]);
+        fs::remove_dir_all(mc_dir).unwrap();
+    }
+}
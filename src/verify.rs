@@ -0,0 +1,117 @@
+use super::downloader::{
+    check_extracted, get_asset_index, get_assets, get_libraries, get_log_config, verify_file,
+    Download, Endpoints,
+};
+use serde_json::Value;
+use std::{fs, path::Path};
+
+// Hashes every file an installed version needs (client jar, libraries, natives and assets)
+// against its json, returning the ones that are missing or corrupted with what is wrong with them.
+pub async fn verify_installation(
+    version: String,
+    endpoints: Endpoints,
+) -> Result<Vec<(Download, String)>, String> {
+    // hashing thousands of assets takes a while, so it runs outside of the async executor.
+    match tokio::task::spawn_blocking(move || get_broken_files(&version, &endpoints)).await {
+        Ok(result) => result,
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+fn get_broken_files(
+    version: &str,
+    endpoints: &Endpoints,
+) -> Result<Vec<(Download, String)>, String> {
+    let mc_dir = super::launcher::get_minecraft_dir();
+    let version_dir = format!("{}/versions/{}", mc_dir, version);
+
+    let version_json = read_json(&format!("{}/{}.json", version_dir, version))?;
+    // loaders keep the vanilla json next to theirs.
+    let vanilla_json = match version_json["inheritsFrom"].as_str() {
+        Some(vanilla) => read_json(&format!("{}/{}.json", version_dir, vanilla))?,
+        None => version_json.clone(),
+    };
+
+    let mut files = vec![Download {
+        path: format!("{}/{}.jar", version_dir, version),
+        url: endpoints.resolve(
+            vanilla_json["downloads"]["client"]["url"]
+                .as_str()
+                .unwrap_or(""),
+        ),
+        sha1: vanilla_json["downloads"]["client"]["sha1"]
+            .as_str()
+            .map(|sha1| sha1.to_string()),
+        size: vanilla_json["downloads"]["client"]["size"].as_u64(),
+        extract: None,
+    }];
+
+    if let Some(log_config) = get_log_config(&mc_dir, &vanilla_json, endpoints)? {
+        files.push(log_config)
+    }
+
+    let mut libraries = vanilla_json["libraries"]
+        .as_array()
+        .cloned()
+        .unwrap_or_default();
+    if !version_json["inheritsFrom"].is_null() {
+        libraries.extend(
+            version_json["libraries"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                // forge's installer makes these with its processors, they can't be downloaded.
+                .filter(|library| library["downloads"]["artifact"]["url"] != "")
+                .cloned(),
+        );
+    }
+    match get_libraries(&mc_dir, &libraries, &version_dir, endpoints) {
+        Ok(ok) => files.extend(ok),
+        Err(e) => return Err(e),
+    }
+
+    let asset_index = get_asset_index(&mc_dir, &vanilla_json, endpoints)?;
+    // without a good index the assets can't be checked, they will be on the next launch.
+    if check_file(&asset_index).is_none() {
+        match get_assets(&mc_dir, read_json(&asset_index.path)?, endpoints) {
+            Ok(ok) => files.extend(ok),
+            Err(e) => return Err(e),
+        }
+    }
+    files.push(asset_index);
+
+    let natives_folder = Path::new(&version_dir).join("natives");
+    let mut broken_files = vec![];
+    for file in files {
+        // natives are compared with the jar they were extracted from, once the jar is known to be
+        // good. Launchers that removed the jars after extracting them get them downloaded again.
+        let problem = match &file.extract {
+            Some(exclude) => {
+                check_file(&file).or_else(|| check_extracted(&file.path, &natives_folder, exclude))
+            }
+            None => check_file(&file),
+        };
+        if let Some(problem) = problem {
+            broken_files.push((file, problem));
+        }
+    }
+    Ok(broken_files)
+}
+
+fn check_file(file: &Download) -> Option<String> {
+    if !Path::new(&file.path).exists() {
+        return Some(format!("{} is missing", file.path));
+    }
+    verify_file(&file.path, file.sha1.as_deref(), file.size).err()
+}
+
+pub fn read_json(path: &str) -> Result<Value, String> {
+    let content = match fs::read_to_string(path) {
+        Ok(ok) => ok,
+        Err(e) => return Err(format!("{}: {}", path, e)),
+    };
+    match serde_json::from_str(&content) {
+        Ok(ok) => Ok(ok),
+        Err(e) => Err(format!("{}: {}", path, e)),
+    }
+}